@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+
+use tauri::{AppHandle, Runtime, State};
+
+use crate::content;
+use crate::db::{self, DownloadRow, EpisodeRow, FeedHealth, Mailbox};
+use crate::directory;
+use crate::discover::{self, FeedCandidate};
+use crate::downloads::{self, DownloadManager};
+use crate::error::{Error, Result};
+use crate::feed::{self, Feed};
+use crate::full_text;
+use crate::icons;
+use crate::image_cache;
+use crate::mail;
+use crate::refresh::{self, RefreshEngine, RefreshOutcome};
+use crate::revisions::{self, DiffChunk, Revision};
+use crate::scheduler::{self, ArticlesUpdated};
+use crate::scrape::{self, Selectors};
+
+#[tauri::command]
+pub fn parse_feed(body: String, content_type: Option<String>) -> Result<Feed> {
+    feed::parse_with_content_type(&body, content_type.as_deref())
+}
+
+#[tauri::command]
+pub async fn refresh_feed<R: Runtime>(
+    app: AppHandle<R>,
+    engine: State<'_, RefreshEngine>,
+    url: String,
+) -> Result<RefreshOutcome> {
+    let pool = db::pool(&app).await?;
+    engine
+        .refresh_all(&pool, vec![url.clone()])
+        .await?
+        .pop()
+        .map(|(_, result)| result)
+        .unwrap_or(Err(Error::FeedNotFound(url)))
+}
+
+/// Subscribes to a feed and stores its current items.
+#[tauri::command]
+pub async fn add_feed<R: Runtime>(
+    app: AppHandle<R>,
+    url: String,
+    name: Option<String>,
+) -> Result<RefreshOutcome> {
+    let pool = db::pool(&app).await?;
+    let outcome = refresh::subscribe(&pool, &url, name.as_deref(), None).await?;
+    refresh_icons_later(app);
+    Ok(outcome)
+}
+
+/// Subscribes to an HTML page that has no feed, scraping it with
+/// `selectors` on every refresh.
+#[tauri::command]
+pub async fn add_scraper_feed<R: Runtime>(
+    app: AppHandle<R>,
+    url: String,
+    name: Option<String>,
+    selectors: Selectors,
+) -> Result<RefreshOutcome> {
+    let pool = db::pool(&app).await?;
+    let outcome = refresh::subscribe(&pool, &url, name.as_deref(), Some(&selectors)).await?;
+    refresh_icons_later(app);
+    Ok(outcome)
+}
+
+/// Scrapes a page without subscribing, to try selectors out.
+#[tauri::command]
+pub async fn preview_scraper_feed(url: String, selectors: Selectors) -> Result<Feed> {
+    scrape::preview(&url, &selectors).await
+}
+
+/// Looks for a new feed's icon without holding up the subscription.
+fn refresh_icons_later<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = icons::refresh_stale(&app).await {
+            log::warn!("could not refresh feed icons: {e}");
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn refresh_all_feeds<R: Runtime>(
+    app: AppHandle<R>,
+    engine: State<'_, RefreshEngine>,
+) -> Result<ArticlesUpdated> {
+    let pool = db::pool(&app).await?;
+    let urls = db::list_feed_schedules(&pool)
+        .await?
+        .into_iter()
+        .map(|feed| feed.url)
+        .collect();
+    let update = scheduler::summarize(engine.refresh_all(&pool, urls).await?);
+    apply_download_rules(&app, &update).await;
+    Ok(update)
+}
+
+#[tauri::command]
+pub async fn refresh_due_feeds<R: Runtime>(
+    app: AppHandle<R>,
+    engine: State<'_, RefreshEngine>,
+) -> Result<ArticlesUpdated> {
+    let pool = db::pool(&app).await?;
+    let update = scheduler::refresh_due(&pool, &engine).await?;
+    apply_download_rules(&app, &update).await;
+    Ok(update)
+}
+
+/// New episodes may fall under a feed's auto-download rule. A failure here
+/// shouldn't fail the refresh that found them.
+async fn apply_download_rules<R: Runtime>(app: &AppHandle<R>, update: &ArticlesUpdated) {
+    if update.new_articles > 0
+        && let Err(e) = downloads::apply_rules(app).await
+    {
+        log::warn!("could not apply download rules: {e}");
+    }
+}
+
+/// Overrides the feed's declared refresh interval; `None` restores it.
+#[tauri::command]
+pub async fn set_refresh_interval<R: Runtime>(
+    app: AppHandle<R>,
+    url: String,
+    minutes: Option<i64>,
+) -> Result<()> {
+    let pool = db::pool(&app).await?;
+    db::set_refresh_interval_override(&pool, &url, minutes.filter(|m| *m > 0)).await
+}
+
+/// Watches `path` for feed files to subscribe to, or stops watching. The
+/// directory is synced right away.
+#[tauri::command]
+pub async fn set_feed_directory<R: Runtime>(
+    app: AppHandle<R>,
+    engine: State<'_, RefreshEngine>,
+    path: Option<String>,
+) -> Result<ArticlesUpdated> {
+    let pool = db::pool(&app).await?;
+    directory::set_directory(&pool, path.as_deref()).await?;
+    directory::sync(&pool, &engine).await
+}
+
+/// Imports newsletters from a Maildir or mbox, now and on every scheduler
+/// pass, as one feed per sender.
+#[tauri::command]
+pub async fn add_mailbox<R: Runtime>(app: AppHandle<R>, path: String) -> Result<ArticlesUpdated> {
+    let pool = db::pool(&app).await?;
+    mail::add_mailbox(&pool, &path).await
+}
+
+/// Stops importing from a mailbox. Feeds and articles already imported stay.
+#[tauri::command]
+pub async fn remove_mailbox<R: Runtime>(app: AppHandle<R>, path: String) -> Result<()> {
+    let pool = db::pool(&app).await?;
+    db::delete_mailbox(&pool, &path).await
+}
+
+#[tauri::command]
+pub async fn list_mailboxes<R: Runtime>(app: AppHandle<R>) -> Result<Vec<Mailbox>> {
+    let pool = db::pool(&app).await?;
+    db::list_mailboxes(&pool).await
+}
+
+#[tauri::command]
+pub async fn discover_feeds(url: String) -> Result<Vec<FeedCandidate>> {
+    discover::discover_feeds(&url).await
+}
+
+/// Feeds whose last refresh failed, worst first, with the error behind each.
+#[tauri::command]
+pub async fn list_unhealthy_feeds<R: Runtime>(app: AppHandle<R>) -> Result<Vec<FeedHealth>> {
+    let pool = db::pool(&app).await?;
+    db::list_unhealthy_feeds(&pool).await
+}
+
+/// An article's earlier versions, oldest first, followed by the current one.
+#[tauri::command]
+pub async fn get_article_revisions<R: Runtime>(
+    app: AppHandle<R>,
+    article_id: String,
+) -> Result<Vec<Revision>> {
+    let pool = db::pool(&app).await?;
+    db::list_revisions(&pool, &article_id).await
+}
+
+/// Diffs two versions of an article; a missing revision id means the
+/// current version.
+#[tauri::command]
+pub async fn diff_article_revisions<R: Runtime>(
+    app: AppHandle<R>,
+    article_id: String,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<Vec<DiffChunk>> {
+    let pool = db::pool(&app).await?;
+    let revisions = db::list_revisions(&pool, &article_id).await?;
+    let find = |id: Option<i64>| {
+        revisions
+            .iter()
+            .find(|r| r.id == id)
+            .ok_or_else(|| Error::RevisionNotFound(id.unwrap_or_default()))
+    };
+    Ok(revisions::diff(find(from)?, find(to)?))
+}
+
+/// Articles with enclosures and their podcast metadata, newest first.
+#[tauri::command]
+pub async fn list_episodes<R: Runtime>(
+    app: AppHandle<R>,
+    feed_url: Option<String>,
+    article_id: Option<String>,
+) -> Result<Vec<EpisodeRow>> {
+    let pool = db::pool(&app).await?;
+    db::list_episodes(&pool, feed_url.as_deref(), article_id.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn download_enclosure<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, DownloadManager>,
+    enclosure_id: i64,
+) -> Result<()> {
+    manager.enqueue(&app, enclosure_id, false).await
+}
+
+#[tauri::command]
+pub async fn delete_download<R: Runtime>(
+    app: AppHandle<R>,
+    manager: State<'_, DownloadManager>,
+    enclosure_id: i64,
+) -> Result<()> {
+    manager.delete(&app, enclosure_id).await
+}
+
+#[tauri::command]
+pub async fn list_downloads<R: Runtime>(app: AppHandle<R>) -> Result<Vec<DownloadRow>> {
+    let pool = db::pool(&app).await?;
+    db::list_downloads(&pool).await
+}
+
+/// Played episodes are the first to go when downloads exceed the quota.
+#[tauri::command]
+pub async fn mark_episode_played<R: Runtime>(app: AppHandle<R>, enclosure_id: i64) -> Result<()> {
+    let pool = db::pool(&app).await?;
+    db::mark_played(&pool, enclosure_id).await
+}
+
+/// Keeps the feed's newest `keep` episodes downloaded; `None` turns it off.
+#[tauri::command]
+pub async fn set_auto_download<R: Runtime>(
+    app: AppHandle<R>,
+    feed_url: String,
+    keep: Option<i64>,
+) -> Result<()> {
+    let pool = db::pool(&app).await?;
+    db::set_auto_download(&pool, &feed_url, keep.filter(|k| *k > 0)).await?;
+    downloads::apply_rules(&app).await
+}
+
+/// Turns full-text extraction on or off for a feed, and starts extracting
+/// its recent articles when it's turned on.
+#[tauri::command]
+pub async fn set_full_text<R: Runtime>(
+    app: AppHandle<R>,
+    feed_url: String,
+    enabled: bool,
+) -> Result<()> {
+    let pool = db::pool(&app).await?;
+    db::set_full_text(&pool, &feed_url, enabled).await?;
+    if enabled {
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = full_text::extract_pending(&app).await {
+                log::warn!("could not extract full text: {e}");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Extracts an article's full text again, for example after a failure.
+#[tauri::command]
+pub async fn extract_article<R: Runtime>(app: AppHandle<R>, article_id: String) -> Result<String> {
+    full_text::extract_article(&app, &article_id).await
+}
+
+#[tauri::command]
+pub async fn set_download_quota<R: Runtime>(app: AppHandle<R>, bytes: Option<i64>) -> Result<()> {
+    downloads::set_quota(&app, bytes).await
+}
+
+#[tauri::command]
+pub async fn set_image_cache_limit<R: Runtime>(
+    app: AppHandle<R>,
+    bytes: Option<i64>,
+) -> Result<()> {
+    image_cache::set_limit(&app, bytes).await
+}
+
+/// Maps feed URLs to addresses the webview can load their icons from.
+#[tauri::command]
+pub async fn list_feed_icons<R: Runtime>(app: AppHandle<R>) -> Result<HashMap<String, String>> {
+    icons::addresses(&app).await
+}
+
+/// Sanitizes HTML the webview stores itself, such as the one-time import
+/// from localStorage, the same way ingest does. Relative URLs resolve
+/// against `base`, usually the article link.
+#[tauri::command]
+pub fn prepare_content(html: String, base: Option<String>) -> String {
+    content::prepare(&html, base.as_deref())
+}
@@ -0,0 +1,76 @@
+use tauri::{AppHandle, Runtime};
+use url::Url;
+
+use crate::db::{self, StoredContent};
+use crate::error::Result;
+use crate::html;
+use crate::image_cache;
+use crate::sanitize;
+
+/// Bumped whenever `prepare` changes, so rows stored by an older version
+/// are brought up to date on the next start.
+const VERSION: i64 = 4;
+const VERSION_KEY: &str = "content_version";
+
+/// What ingest does to article HTML before storing it: make URLs absolute
+/// against `base`, point images at the local cache, then strip anything
+/// unsafe. The sanitizer goes last so what's stored is exactly its output.
+pub fn prepare(content: &str, base: Option<&str>) -> String {
+    let resolved = match base.and_then(|b| Url::parse(b).ok()) {
+        Some(base) => html::resolve_urls(content, &base),
+        None => content.to_string(),
+    };
+    sanitize::clean(&image_cache::rewrite(&resolved))
+}
+
+/// Runs stored articles through `prepare` if they predate the current
+/// version.
+pub fn start<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = reprocess_stored(&app).await {
+            log::warn!("could not update stored articles: {e}");
+        }
+    });
+}
+
+async fn reprocess_stored<R: Runtime>(app: &AppHandle<R>) -> Result<()> {
+    let pool = db::pool(app).await?;
+    let stored_version = db::get_meta(&pool, VERSION_KEY)
+        .await?
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(0);
+    if stored_version >= VERSION {
+        return Ok(());
+    }
+    for stored in db::list_stored_content(&pool).await? {
+        let base = stored.link.as_deref().and_then(|l| Url::parse(l).ok());
+        let image = |url: &str| {
+            let url = base
+                .as_ref()
+                .and_then(|b| b.join(url).ok())
+                .map_or_else(|| url.to_string(), String::from);
+            image_cache::proxied(&url)
+        };
+        let prepared = StoredContent {
+            content: stored
+                .content
+                .as_deref()
+                .map(|c| prepare(c, stored.link.as_deref())),
+            full_content: stored
+                .full_content
+                .as_deref()
+                .map(|c| prepare(c, stored.link.as_deref())),
+            lead_image: stored.lead_image.as_deref().map(image),
+            episode_image: stored.episode_image.as_deref().map(image),
+            ..stored.clone()
+        };
+        if prepared.content != stored.content
+            || prepared.full_content != stored.full_content
+            || prepared.lead_image != stored.lead_image
+            || prepared.episode_image != stored.episode_image
+        {
+            db::set_stored_content(&pool, &prepared).await?;
+        }
+    }
+    db::set_meta(&pool, VERSION_KEY, Some(&VERSION.to_string())).await
+}
@@ -0,0 +1,286 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+const ISO_WITH_OFFSET: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+    "%Y-%m-%dT%H:%M%z",
+    "%Y-%m-%d %H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M%z",
+];
+
+const ISO_NAIVE: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Month names and abbreviations in the languages feeds most often use,
+/// lowercase and without trailing dots.
+const MONTHS: [&[&str]; 12] = [
+    &[
+        "jan", "january", "janv", "janvier", "januar", "ene", "enero", "gen", "gennaio", "januari",
+        "janeiro",
+    ],
+    &[
+        "feb",
+        "february",
+        "fév",
+        "févr",
+        "février",
+        "fev",
+        "fevr",
+        "fevrier",
+        "februar",
+        "febrero",
+        "febbraio",
+        "februari",
+        "fevereiro",
+    ],
+    &[
+        "mar", "march", "mars", "mär", "märz", "mrz", "marz", "marzo", "maart", "mrt", "março",
+        "marco",
+    ],
+    &["apr", "april", "avr", "avril", "abr", "abril", "aprile"],
+    &["may", "mai", "mayo", "mag", "maggio", "mei", "maio"],
+    &[
+        "jun", "june", "juin", "juni", "junio", "giu", "giugno", "junho",
+    ],
+    &[
+        "jul", "july", "juil", "juillet", "juli", "julio", "lug", "luglio", "julho",
+    ],
+    &["aug", "august", "août", "aout", "ago", "agosto", "augustus"],
+    &[
+        "sep",
+        "sept",
+        "september",
+        "septembre",
+        "set",
+        "septiembre",
+        "settembre",
+        "setembro",
+    ],
+    &[
+        "oct", "october", "octobre", "okt", "oktober", "octubre", "ott", "ottobre", "out",
+        "outubro",
+    ],
+    &["nov", "november", "novembre", "noviembre", "novembro"],
+    &[
+        "dec",
+        "december",
+        "déc",
+        "décembre",
+        "decembre",
+        "dez",
+        "dezember",
+        "dic",
+        "diciembre",
+        "dicembre",
+        "dezembro",
+    ],
+];
+
+/// Zone abbreviations seen in the wild, in minutes east of UTC. Ambiguous
+/// ones take their most common meaning in feeds (IST is India).
+const ZONES: [(&str, i32); 38] = [
+    ("ut", 0),
+    ("utc", 0),
+    ("gmt", 0),
+    ("z", 0),
+    ("est", -5 * 60),
+    ("edt", -4 * 60),
+    ("cst", -6 * 60),
+    ("cdt", -5 * 60),
+    ("mst", -7 * 60),
+    ("mdt", -6 * 60),
+    ("pst", -8 * 60),
+    ("pdt", -7 * 60),
+    ("akst", -9 * 60),
+    ("akdt", -8 * 60),
+    ("hst", -10 * 60),
+    ("ast", -4 * 60),
+    ("adt", -3 * 60),
+    ("nst", -(3 * 60 + 30)),
+    ("ndt", -(2 * 60 + 30)),
+    ("wet", 0),
+    ("west", 60),
+    ("bst", 60),
+    ("cet", 60),
+    ("met", 60),
+    ("cest", 2 * 60),
+    ("mest", 2 * 60),
+    ("eet", 2 * 60),
+    ("eest", 3 * 60),
+    ("msk", 3 * 60),
+    ("ist", 5 * 60 + 30),
+    ("sgt", 8 * 60),
+    ("hkt", 8 * 60),
+    ("awst", 8 * 60),
+    ("jst", 9 * 60),
+    ("kst", 9 * 60),
+    ("acst", 9 * 60 + 30),
+    ("aest", 10 * 60),
+    ("aedt", 11 * 60),
+];
+
+/// Normalizes a feed date to milliseconds since the epoch.
+pub fn timestamp_ms(value: &str) -> Option<i64> {
+    parse(value).map(|d| d.timestamp_millis())
+}
+
+/// Parses RFC 2822 and RFC 3339 dates plus the broken variants feeds
+/// actually publish: named time zones, two-digit years, missing seconds,
+/// missing or misspelled weekdays and non-English month names.
+pub fn parse(value: &str) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_rfc2822(value))
+        .ok()
+        .or_else(|| parse_iso(value))
+        .or_else(|| parse_loose(value))
+        // The epoch and anything before it is a placeholder, not a real date.
+        .filter(|d| d.timestamp() > 0)
+}
+
+fn parse_iso(value: &str) -> Option<DateTime<FixedOffset>> {
+    let value = value
+        .strip_suffix(['Z', 'z'])
+        .map_or_else(|| value.to_string(), |v| format!("{v}+00:00"));
+    let value = value.as_str();
+    ISO_WITH_OFFSET
+        .iter()
+        .find_map(|f| DateTime::parse_from_str(value, f).ok())
+        .or_else(|| {
+            ISO_NAIVE
+                .iter()
+                .find_map(|f| NaiveDateTime::parse_from_str(value, f).ok())
+                .or_else(|| {
+                    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                        .ok()
+                        .map(|d| d.and_time(NaiveTime::MIN))
+                })
+                .map(|d| Utc.from_utc_datetime(&d).fixed_offset())
+        })
+}
+
+/// Picks the day, month, year, time and zone out of an RFC 822-ish string
+/// in whatever order they come, ignoring words it doesn't know.
+fn parse_loose(value: &str) -> Option<DateTime<FixedOffset>> {
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+    let mut time = None;
+    let mut offset = None;
+
+    // A leading word followed by a comma is a weekday, so "mar, 10 jun" is a
+    // Spanish Tuesday rather than March.
+    let value = match value.split_once(',') {
+        Some((weekday, rest))
+            if weekday
+                .trim()
+                .trim_end_matches('.')
+                .chars()
+                .all(char::is_alphabetic) =>
+        {
+            rest
+        }
+        _ => value,
+    };
+    let tokens = value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|t| !t.is_empty());
+
+    for token in tokens {
+        if token.contains(':') && time.is_none() {
+            let (clock, zone) = split_zone(token);
+            time = Some(parse_time(clock)?);
+            if let Some(zone) = zone {
+                offset = Some(zone);
+            }
+        } else if token.starts_with(['+', '-']) {
+            offset = numeric_offset(token).or(offset);
+        } else if token.chars().all(|c| c.is_ascii_digit()) {
+            let n: i32 = token.parse().ok()?;
+            if token.len() == 4 {
+                year = Some(n);
+            } else if day.is_none() {
+                day = Some(n as u32);
+            } else if year.is_none() {
+                year = Some(expand_year(n));
+            }
+        } else {
+            let word = token.trim_end_matches('.').to_lowercase();
+            if month.is_none()
+                && let Some(m) = month_number(&word)
+            {
+                month = Some(m);
+            } else if let Some(zone) = zone_offset(&word) {
+                offset = Some(zone);
+            }
+        }
+    }
+
+    let date = NaiveDate::from_ymd_opt(year?, month?, day?)?;
+    let naive = date.and_time(time.unwrap_or(NaiveTime::MIN));
+    let offset = offset.unwrap_or(FixedOffset::east_opt(0)?);
+    offset.from_local_datetime(&naive).single()
+}
+
+/// RFC 2822's rule for obsolete two-digit years.
+fn expand_year(year: i32) -> i32 {
+    match year {
+        0..50 => 2000 + year,
+        50..100 => 1900 + year,
+        _ => year,
+    }
+}
+
+/// Splits "10:00:00+0100" or "10:00Z" into the clock and its zone.
+fn split_zone(token: &str) -> (&str, Option<FixedOffset>) {
+    if let Some(clock) = token.strip_suffix(['Z', 'z']) {
+        return (clock, FixedOffset::east_opt(0));
+    }
+    match token.rfind(['+', '-']) {
+        Some(i) => (&token[..i], numeric_offset(&token[i..])),
+        None => (token, None),
+    }
+}
+
+fn parse_time(clock: &str) -> Option<NaiveTime> {
+    // Drop fractional seconds.
+    let clock = clock.split('.').next()?;
+    let mut parts = clock.split(':').map(|p| p.parse::<u32>().ok());
+    let hour = parts.next()??;
+    let minute = parts.next()??;
+    let second = parts.next().flatten().unwrap_or(0);
+    // Leap seconds and 24:00 both show up; clamp rather than reject.
+    NaiveTime::from_hms_opt(hour.min(23), minute, second.min(59))
+}
+
+/// "+0530", "+05:30", "-05" or "-5".
+fn numeric_offset(token: &str) -> Option<FixedOffset> {
+    let sign = if token.starts_with('-') { -1 } else { 1 };
+    let digits: String = token[1..].chars().filter(|c| *c != ':').collect();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        1 | 2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (digits[..2].parse().ok()?, digits[2..].parse().ok()?),
+        _ => return None,
+    };
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+fn month_number(word: &str) -> Option<u32> {
+    MONTHS
+        .iter()
+        .position(|names| names.contains(&word))
+        .map(|i| i as u32 + 1)
+}
+
+fn zone_offset(word: &str) -> Option<FixedOffset> {
+    ZONES
+        .iter()
+        .find(|(name, _)| *name == word)
+        .and_then(|(_, minutes)| FixedOffset::east_opt(minutes * 60))
+}
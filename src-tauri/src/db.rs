@@ -0,0 +1,1635 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use sqlx::{Pool, Sqlite, Transaction};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_sql::{DbInstances, DbPool, Migration, MigrationKind};
+
+use crate::content;
+use crate::downloads::Progress;
+use crate::error::{Error, Result};
+use crate::feed::{self, Enclosure, Item, Schedule};
+use crate::image_cache;
+use crate::mail::MailboxKind;
+use crate::revisions::{self, Revision};
+use crate::scrape::Selectors;
+
+pub const DB_URL: &str = "sqlite:lector.db";
+
+/// Articles kept per feed, not counting starred ones.
+const MAX_ARTICLES_PER_FEED: i64 = 500;
+
+/// The schema, applied by the SQL plugin when it opens the database.
+pub fn migrations() -> Vec<Migration> {
+    vec![
+        Migration {
+            version: 1,
+            description: "create_initial_tables",
+            sql: "CREATE TABLE IF NOT EXISTS feeds (
+                url TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                added_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS articles (
+                id TEXT PRIMARY KEY,
+                feed_url TEXT NOT NULL REFERENCES feeds(url) ON DELETE CASCADE,
+                feed_name TEXT,
+                title TEXT NOT NULL,
+                link TEXT,
+                published TEXT,
+                published_ts INTEGER,
+                content TEXT,
+                author TEXT,
+                is_read INTEGER NOT NULL DEFAULT 0,
+                is_starred INTEGER NOT NULL DEFAULT 0,
+                fetched_at INTEGER NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_articles_feed_url ON articles(feed_url);
+            CREATE INDEX IF NOT EXISTS idx_articles_published_ts ON articles(published_ts);
+            CREATE INDEX IF NOT EXISTS idx_articles_starred ON articles(is_starred);
+
+            CREATE TABLE IF NOT EXISTS meta (
+                key TEXT PRIMARY KEY,
+                value TEXT
+            );",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 2,
+            description: "add_feed_cache_validators",
+            sql: "ALTER TABLE feeds ADD COLUMN etag TEXT;
+            ALTER TABLE feeds ADD COLUMN last_modified TEXT;
+            ALTER TABLE feeds ADD COLUMN last_fetched_at INTEGER;",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 3,
+            description: "add_feed_refresh_intervals",
+            sql: "ALTER TABLE feeds ADD COLUMN refresh_interval INTEGER;
+            ALTER TABLE feeds ADD COLUMN refresh_interval_override INTEGER;
+            ALTER TABLE feeds ADD COLUMN skip_hours TEXT;
+            ALTER TABLE feeds ADD COLUMN skip_days TEXT;",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 4,
+            description: "add_feed_health",
+            sql: "ALTER TABLE feeds ADD COLUMN last_error TEXT;
+            ALTER TABLE feeds ADD COLUMN last_error_status INTEGER;
+            ALTER TABLE feeds ADD COLUMN last_error_at INTEGER;
+            ALTER TABLE feeds ADD COLUMN consecutive_failures INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE feeds ADD COLUMN next_retry_at INTEGER;",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 5,
+            description: "add_feed_retired_at",
+            sql: "ALTER TABLE feeds ADD COLUMN retired_at INTEGER;",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 6,
+            description: "add_article_revisions",
+            sql: "ALTER TABLE articles ADD COLUMN content_hash TEXT;
+            ALTER TABLE articles ADD COLUMN updated_at INTEGER;
+
+            CREATE TABLE IF NOT EXISTS article_revisions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                article_id TEXT NOT NULL REFERENCES articles(id) ON DELETE CASCADE ON UPDATE CASCADE,
+                title TEXT NOT NULL,
+                content TEXT,
+                content_hash TEXT NOT NULL,
+                replaced_at INTEGER NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_article_revisions_article_id ON article_revisions(article_id);",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 7,
+            description: "add_enclosures",
+            sql: "CREATE TABLE IF NOT EXISTS enclosures (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                article_id TEXT NOT NULL REFERENCES articles(id) ON DELETE CASCADE ON UPDATE CASCADE,
+                url TEXT NOT NULL,
+                mime_type TEXT,
+                length INTEGER,
+                duration INTEGER,
+                UNIQUE(article_id, url)
+            );
+
+            ALTER TABLE articles ADD COLUMN episode INTEGER;
+            ALTER TABLE articles ADD COLUMN season INTEGER;
+            ALTER TABLE articles ADD COLUMN episode_image TEXT;
+            ALTER TABLE articles ADD COLUMN explicit INTEGER;",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 8,
+            description: "add_downloads",
+            sql: "CREATE TABLE IF NOT EXISTS downloads (
+                enclosure_id INTEGER PRIMARY KEY REFERENCES enclosures(id) ON DELETE CASCADE,
+                status TEXT NOT NULL,
+                path TEXT,
+                bytes_downloaded INTEGER NOT NULL DEFAULT 0,
+                total_bytes INTEGER,
+                error TEXT,
+                auto INTEGER NOT NULL DEFAULT 0,
+                requested_at INTEGER NOT NULL,
+                completed_at INTEGER,
+                played_at INTEGER
+            );
+
+            ALTER TABLE feeds ADD COLUMN auto_download_keep INTEGER;",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 9,
+            description: "add_article_lead_image",
+            sql: "ALTER TABLE articles ADD COLUMN lead_image TEXT;",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 10,
+            description: "add_image_cache",
+            sql: "CREATE TABLE IF NOT EXISTS image_cache (
+                url TEXT PRIMARY KEY,
+                file TEXT NOT NULL,
+                content_type TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                last_used_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_image_cache_last_used ON image_cache(last_used_at);",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 11,
+            description: "add_feed_icons",
+            sql: "ALTER TABLE feeds ADD COLUMN site_url TEXT;
+            ALTER TABLE feeds ADD COLUMN icon_url TEXT;
+
+            CREATE TABLE IF NOT EXISTS feed_icons (
+                feed_url TEXT PRIMARY KEY REFERENCES feeds(url) ON DELETE CASCADE ON UPDATE CASCADE,
+                source_url TEXT,
+                content_type TEXT,
+                data BLOB,
+                fetched_at INTEGER NOT NULL
+            );",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 12,
+            description: "add_full_text",
+            sql: "ALTER TABLE feeds ADD COLUMN full_text INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE articles ADD COLUMN full_content TEXT;
+            ALTER TABLE articles ADD COLUMN full_content_error TEXT;
+            ALTER TABLE articles ADD COLUMN full_content_at INTEGER;",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 13,
+            description: "add_scraper_feeds",
+            sql: "CREATE TABLE IF NOT EXISTS scraper_feeds (
+                feed_url TEXT PRIMARY KEY REFERENCES feeds(url) ON DELETE CASCADE ON UPDATE CASCADE,
+                item_selector TEXT NOT NULL,
+                title_selector TEXT,
+                link_selector TEXT,
+                date_selector TEXT,
+                content_selector TEXT
+            );",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 14,
+            description: "add_mailboxes",
+            sql: "CREATE TABLE IF NOT EXISTS mailboxes (
+                path TEXT PRIMARY KEY,
+                kind TEXT NOT NULL,
+                modified_at INTEGER,
+                added_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS mail_messages (
+                message_id TEXT PRIMARY KEY,
+                feed_url TEXT NOT NULL,
+                consumed_at INTEGER NOT NULL
+            );",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 15,
+            description: "add_mailbox_errors",
+            sql: "ALTER TABLE mailboxes ADD COLUMN last_error TEXT;",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 16,
+            description: "mark_legacy_article_ids",
+            // Only the webview wrote articles without a content hash, under
+            // its old link-or-title ids; see `adopt_legacy_ids`.
+            sql: "ALTER TABLE feeds ADD COLUMN legacy_ids INTEGER NOT NULL DEFAULT 0;
+            UPDATE feeds SET legacy_ids = 1
+              WHERE url IN (SELECT feed_url FROM articles WHERE content_hash IS NULL);",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 17,
+            description: "add_directory_files",
+            // Files in the watched directory that aren't subscribed to:
+            // ones that failed to parse, as of their modification time, and
+            // ones the user unsubscribed from. The webview deletes feeds
+            // itself, so a trigger records the latter.
+            sql: "CREATE TABLE IF NOT EXISTS directory_files (
+                url TEXT PRIMARY KEY,
+                modified_at INTEGER,
+                last_error TEXT,
+                last_error_at INTEGER,
+                unsubscribed_at INTEGER
+            );
+            CREATE TRIGGER IF NOT EXISTS remember_unsubscribed_files
+            AFTER DELETE ON feeds WHEN old.url LIKE 'file:%'
+            BEGIN
+              INSERT INTO directory_files (url, unsubscribed_at)
+                VALUES (old.url, CAST(strftime('%s', 'now') AS INTEGER) * 1000)
+                ON CONFLICT(url) DO UPDATE SET unsubscribed_at = excluded.unsubscribed_at;
+            END;
+            CREATE TRIGGER IF NOT EXISTS forget_subscribed_files
+            AFTER INSERT ON feeds WHEN new.url LIKE 'file:%'
+            BEGIN
+              DELETE FROM directory_files WHERE url = new.url;
+            END;",
+            kind: MigrationKind::Up,
+        },
+    ]
+}
+
+/// Borrows the pool the SQL plugin opened for the webview, so both sides
+/// share one database and one set of migrations.
+pub async fn pool<R: Runtime>(app: &AppHandle<R>) -> Result<Pool<Sqlite>> {
+    let instances = app.state::<DbInstances>();
+    let instances = instances.0.read().await;
+    match instances.get(DB_URL) {
+        Some(DbPool::Sqlite(pool)) => Ok(pool.clone()),
+        None => Err(Error::DatabaseNotLoaded),
+    }
+}
+
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+pub struct FeedRow {
+    pub url: String,
+    pub name: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+pub async fn get_feed(pool: &Pool<Sqlite>, url: &str) -> Result<FeedRow> {
+    let row: Option<(String, String, Option<String>, Option<String>)> =
+        sqlx::query_as("SELECT url, name, etag, last_modified FROM feeds WHERE url = $1")
+            .bind(url)
+            .fetch_optional(pool)
+            .await?;
+    let (url, name, etag, last_modified) =
+        row.ok_or_else(|| Error::FeedNotFound(url.to_string()))?;
+    Ok(FeedRow {
+        url,
+        name,
+        etag,
+        last_modified,
+    })
+}
+
+pub async fn mark_fetched(
+    pool: &Pool<Sqlite>,
+    url: &str,
+    etag: Option<&str>,
+    last_modified: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE feeds SET etag = $2, last_modified = $3, last_fetched_at = $4 WHERE url = $1",
+    )
+    .bind(url)
+    .bind(etag)
+    .bind(last_modified)
+    .bind(now_ms())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn touch_fetched(pool: &Pool<Sqlite>, url: &str) -> Result<()> {
+    sqlx::query("UPDATE feeds SET last_fetched_at = $2 WHERE url = $1")
+        .bind(url)
+        .bind(now_ms())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub struct FeedSchedule {
+    pub url: String,
+    pub last_fetched_at: Option<i64>,
+    pub refresh_interval: Option<i64>,
+    pub refresh_interval_override: Option<i64>,
+    pub skip_hours: Vec<u8>,
+    pub skip_days: Vec<String>,
+    pub next_retry_at: Option<i64>,
+}
+
+type ScheduleRow = (
+    String,
+    Option<i64>,
+    Option<i64>,
+    Option<i64>,
+    Option<String>,
+    Option<String>,
+    Option<i64>,
+);
+
+pub async fn list_feed_schedules(pool: &Pool<Sqlite>) -> Result<Vec<FeedSchedule>> {
+    let rows: Vec<ScheduleRow> = sqlx::query_as(
+        "SELECT url, last_fetched_at, refresh_interval, refresh_interval_override, skip_hours, skip_days, next_retry_at
+         FROM feeds WHERE retired_at IS NULL AND url NOT LIKE 'mailto:%' ORDER BY added_at ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(
+            |(
+                url,
+                last_fetched_at,
+                refresh_interval,
+                refresh_interval_override,
+                hours,
+                days,
+                next_retry_at,
+            )| {
+                FeedSchedule {
+                    url,
+                    last_fetched_at,
+                    refresh_interval,
+                    refresh_interval_override,
+                    skip_hours: split_list(hours)
+                        .iter()
+                        .filter_map(|h| h.parse().ok())
+                        .collect(),
+                    skip_days: split_list(days),
+                    next_retry_at,
+                }
+            },
+        )
+        .collect())
+}
+
+/// Stores what the feed itself declares; the user's override lives in its
+/// own column so a refresh never clobbers it.
+pub async fn update_schedule(pool: &Pool<Sqlite>, url: &str, schedule: &Schedule) -> Result<()> {
+    let hours: Vec<String> = schedule.skip_hours.iter().map(u8::to_string).collect();
+    sqlx::query(
+        "UPDATE feeds SET refresh_interval = $2, skip_hours = $3, skip_days = $4 WHERE url = $1",
+    )
+    .bind(url)
+    .bind(schedule.interval_minutes.map(i64::from))
+    .bind(join_list(&hours))
+    .bind(join_list(&schedule.skip_days))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn set_refresh_interval_override(
+    pool: &Pool<Sqlite>,
+    url: &str,
+    minutes: Option<i64>,
+) -> Result<()> {
+    let result = sqlx::query("UPDATE feeds SET refresh_interval_override = $2 WHERE url = $1")
+        .bind(url)
+        .bind(minutes)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(Error::FeedNotFound(url.to_string()));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedHealth {
+    pub url: String,
+    pub name: String,
+    pub last_error: Option<String>,
+    pub last_error_status: Option<i64>,
+    pub last_error_at: Option<i64>,
+    pub consecutive_failures: i64,
+    pub next_retry_at: Option<i64>,
+    pub retired_at: Option<i64>,
+}
+
+pub async fn list_unhealthy_feeds(pool: &Pool<Sqlite>) -> Result<Vec<FeedHealth>> {
+    let feeds = sqlx::query_as(
+        "SELECT url, name, last_error, last_error_status, last_error_at, consecutive_failures, next_retry_at, retired_at
+         FROM feeds WHERE consecutive_failures > 0 OR retired_at IS NOT NULL
+         ORDER BY consecutive_failures DESC, last_error_at DESC",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(feeds)
+}
+
+pub async fn record_success(pool: &Pool<Sqlite>, url: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE feeds SET last_error = NULL, last_error_status = NULL, last_error_at = NULL,
+           consecutive_failures = 0, next_retry_at = NULL
+         WHERE url = $1",
+    )
+    .bind(url)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Records a failed refresh and returns the feed's new failure count.
+pub async fn record_failure(
+    pool: &Pool<Sqlite>,
+    url: &str,
+    error: &str,
+    status: Option<u16>,
+) -> Result<i64> {
+    let failures: Option<i64> = sqlx::query_scalar(
+        "UPDATE feeds SET last_error = $2, last_error_status = $3, last_error_at = $4,
+           consecutive_failures = consecutive_failures + 1
+         WHERE url = $1
+         RETURNING consecutive_failures",
+    )
+    .bind(url)
+    .bind(error)
+    .bind(status.map(i64::from))
+    .bind(now_ms())
+    .fetch_optional(pool)
+    .await?;
+    Ok(failures.unwrap_or(0))
+}
+
+pub async fn set_next_retry(pool: &Pool<Sqlite>, url: &str, at: i64) -> Result<()> {
+    sqlx::query("UPDATE feeds SET next_retry_at = $2 WHERE url = $1")
+        .bind(url)
+        .bind(at)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Stops refreshing a feed the server says is gone for good. The feed and
+/// its articles stay until the user unsubscribes.
+pub async fn retire_feed(pool: &Pool<Sqlite>, url: &str) -> Result<()> {
+    sqlx::query("UPDATE feeds SET retired_at = $2 WHERE url = $1")
+        .bind(url)
+        .bind(now_ms())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Resumes refreshing a retired feed.
+pub async fn restore_feed(pool: &Pool<Sqlite>, url: &str) -> Result<()> {
+    sqlx::query("UPDATE feeds SET retired_at = NULL WHERE url = $1")
+        .bind(url)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Subscriptions to local files, with whether each is retired.
+pub async fn list_file_feeds(pool: &Pool<Sqlite>) -> Result<Vec<(String, bool)>> {
+    Ok(
+        sqlx::query_as("SELECT url, retired_at IS NOT NULL FROM feeds WHERE url LIKE 'file:%'")
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DirectoryFile {
+    pub url: String,
+    pub modified_at: Option<i64>,
+    pub last_error: Option<String>,
+    pub unsubscribed_at: Option<i64>,
+}
+
+/// Files the directory sync knows not to subscribe to, either because
+/// they failed to parse or because the user unsubscribed from them.
+pub async fn list_directory_files(pool: &Pool<Sqlite>) -> Result<Vec<DirectoryFile>> {
+    Ok(
+        sqlx::query_as("SELECT url, modified_at, last_error, unsubscribed_at FROM directory_files")
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+/// Records that a file in the watched directory couldn't be subscribed to,
+/// so it isn't retried until its modification time changes.
+pub async fn record_unreadable_file(
+    pool: &Pool<Sqlite>,
+    url: &str,
+    modified_at: Option<i64>,
+    error: &str,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO directory_files (url, modified_at, last_error, last_error_at)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT(url) DO UPDATE SET modified_at = excluded.modified_at,
+           last_error = excluded.last_error, last_error_at = excluded.last_error_at",
+    )
+    .bind(url)
+    .bind(modified_at)
+    .bind(error)
+    .bind(now_ms())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Rewrites a feed's URL, which is also the prefix of its article ids, after
+/// a permanent redirect. When the user already subscribes to the new URL the
+/// two feeds are merged and read/starred state on the surviving rows wins.
+pub async fn move_feed(pool: &Pool<Sqlite>, old_url: &str, new_url: &str) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    // articles.feed_url references feeds.url, so let the rows be out of step
+    // until the transaction commits.
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "UPDATE OR IGNORE articles SET
+           id = CASE WHEN substr(id, 1, length($1) + 2) = $1 || '::'
+                THEN $2 || substr(id, length($1) + 1) ELSE id END,
+           feed_url = $2
+         WHERE feed_url = $1",
+    )
+    .bind(old_url)
+    .bind(new_url)
+    .execute(&mut *tx)
+    .await?;
+    // Whatever is left collided with an article the new feed already has.
+    sqlx::query("DELETE FROM articles WHERE feed_url = $1")
+        .bind(old_url)
+        .execute(&mut *tx)
+        .await?;
+    let already_subscribed: Option<i64> = sqlx::query_scalar("SELECT 1 FROM feeds WHERE url = $1")
+        .bind(new_url)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if already_subscribed.is_some() {
+        sqlx::query("DELETE FROM feeds WHERE url = $1")
+            .bind(old_url)
+            .execute(&mut *tx)
+            .await?;
+    } else {
+        sqlx::query("UPDATE feeds SET url = $2 WHERE url = $1")
+            .bind(old_url)
+            .bind(new_url)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+fn split_list(value: Option<String>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+fn join_list(values: &[String]) -> Option<String> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(","))
+    }
+}
+
+pub async fn get_meta(pool: &Pool<Sqlite>, key: &str) -> Result<Option<String>> {
+    let value: Option<Option<String>> = sqlx::query_scalar("SELECT value FROM meta WHERE key = $1")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+    Ok(value.flatten())
+}
+
+/// Stores a feed's items, keeping revisions of edited ones and pruning old
+/// ones. Returns how many articles were new.
+pub async fn upsert_articles(
+    pool: &Pool<Sqlite>,
+    feed_url: &str,
+    feed_name: &str,
+    items: &[Item],
+) -> Result<usize> {
+    let mut tx = pool.begin().await?;
+    let inserted = store_articles(&mut tx, feed_url, feed_name, items).await?;
+    tx.commit().await?;
+    Ok(inserted)
+}
+
+async fn store_articles(
+    tx: &mut Transaction<'_, Sqlite>,
+    feed_url: &str,
+    feed_name: &str,
+    items: &[Item],
+) -> Result<usize> {
+    let now = now_ms();
+    let mut inserted = 0;
+    let has_legacy_ids =
+        sqlx::query("UPDATE feeds SET legacy_ids = 0 WHERE url = $1 AND legacy_ids = 1")
+            .bind(feed_url)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected()
+            > 0;
+    if has_legacy_ids {
+        adopt_legacy_ids(tx, feed_url, items).await?;
+    }
+    for item in items {
+        let id = format!("{feed_url}::{}", item.id);
+        let hash = revisions::content_hash(&item.title, item.content.as_deref());
+        let updated_at = match stored_version(tx, &id).await? {
+            None => {
+                inserted += 1;
+                None
+            }
+            // A row without a hash was stored by the webview from its own
+            // parse, so it isn't comparable; the new hash just becomes the
+            // baseline.
+            Some(stored) if stored.hash.as_ref().is_some_and(|h| *h != hash) => {
+                record_revision(tx, &id, &stored, now).await?;
+                Some(now)
+            }
+            Some(_) => None,
+        };
+        // Undated articles sort by when we first saw them, which an update
+        // must not move.
+        sqlx::query(
+            "INSERT INTO articles (id, feed_url, feed_name, title, link, published, published_ts, content, author, fetched_at, content_hash,
+               episode, season, episode_image, explicit, lead_image)
+             VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, $10), $8, $9, $10, $11, $13, $14, $15, $16, $17)
+             ON CONFLICT(id) DO UPDATE SET
+               feed_name = $3, title = $4, link = $5, published = $6,
+               published_ts = COALESCE($7, NULLIF(articles.published_ts, 0), $10),
+               content = $8, author = $9, fetched_at = $10, content_hash = $11,
+               updated_at = COALESCE($12, articles.updated_at),
+               episode = $13, season = $14, episode_image = $15, explicit = $16,
+               lead_image = $17",
+        )
+        .bind(&id)
+        .bind(feed_url)
+        .bind(feed_name)
+        .bind(&item.title)
+        .bind(&item.link)
+        .bind(&item.published)
+        .bind(item.published_ts)
+        .bind(
+            item.content
+                .as_deref()
+                .map(|c| content::prepare(c, item.base.as_deref())),
+        )
+        .bind(&item.author)
+        .bind(now)
+        .bind(&hash)
+        .bind(updated_at)
+        .bind(item.episode.number)
+        .bind(item.episode.season)
+        .bind(item.episode.image.as_deref().map(image_cache::proxied))
+        .bind(item.episode.explicit)
+        .bind(item.image.as_deref().map(image_cache::proxied))
+        .execute(&mut **tx)
+        .await?;
+        replace_enclosures(tx, &id, &item.enclosures).await?;
+    }
+    sqlx::query(
+        "DELETE FROM articles
+         WHERE is_starred = 0
+           AND feed_url = $1
+           AND id NOT IN (
+             SELECT id FROM articles
+             WHERE is_starred = 0 AND feed_url = $1
+             ORDER BY published_ts DESC, fetched_at DESC
+             LIMIT $2
+           )",
+    )
+    .bind(feed_url)
+    .bind(MAX_ARTICLES_PER_FEED)
+    .execute(&mut **tx)
+    .await?;
+    Ok(inserted)
+}
+
+/// Articles the webview stored before ids came from guids were keyed on
+/// their link, or their title when they had none, and carry no content
+/// hash. This runs once per feed, on its first refresh since: rows whose
+/// item is still in the feed take that item's id and the rest the id an
+/// item without a guid would get, so every row keeps its read and starred
+/// state.
+async fn adopt_legacy_ids(
+    tx: &mut Transaction<'_, Sqlite>,
+    feed_url: &str,
+    items: &[Item],
+) -> Result<()> {
+    let live: HashMap<&str, &str> = items
+        .iter()
+        .map(|item| {
+            let key = legacy_key(item.original_link.as_deref(), &item.title);
+            (key, item.id.as_str())
+        })
+        .collect();
+    let rows: Vec<(String, Option<String>, String, Option<String>)> = sqlx::query_as(
+        "SELECT id, link, title, published FROM articles
+         WHERE feed_url = $1 AND content_hash IS NULL",
+    )
+    .bind(feed_url)
+    .fetch_all(&mut **tx)
+    .await?;
+    for (old_id, link, title, published) in rows {
+        let item_id = match live.get(legacy_key(link.as_deref(), &title)) {
+            Some(id) => id.to_string(),
+            None => feed::hashed_id(
+                link.as_deref().map(str::trim).filter(|l| !l.is_empty()),
+                title.trim(),
+                published.as_deref().map(str::trim),
+            ),
+        };
+        let new_id = format!("{feed_url}::{item_id}");
+        if new_id == old_id {
+            continue;
+        }
+        let renamed = sqlx::query("UPDATE OR IGNORE articles SET id = $2 WHERE id = $1")
+            .bind(&old_id)
+            .bind(&new_id)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected();
+        if renamed == 0 {
+            // Another row already has the id; keep the state of both.
+            sqlx::query(
+                "UPDATE articles SET
+                   is_read = MAX(is_read, (SELECT is_read FROM articles WHERE id = $1)),
+                   is_starred = MAX(is_starred, (SELECT is_starred FROM articles WHERE id = $1))
+                 WHERE id = $2",
+            )
+            .bind(&old_id)
+            .bind(&new_id)
+            .execute(&mut **tx)
+            .await?;
+            sqlx::query("DELETE FROM articles WHERE id = $1")
+                .bind(&old_id)
+                .execute(&mut **tx)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+fn legacy_key<'a>(link: Option<&'a str>, title: &'a str) -> &'a str {
+    link.map(str::trim)
+        .filter(|link| !link.is_empty())
+        .unwrap_or(title.trim())
+}
+
+struct StoredVersion {
+    title: String,
+    content: Option<String>,
+    hash: Option<String>,
+}
+
+async fn stored_version(
+    tx: &mut Transaction<'_, Sqlite>,
+    id: &str,
+) -> Result<Option<StoredVersion>> {
+    let row: Option<(String, Option<String>, Option<String>)> =
+        sqlx::query_as("SELECT title, content, content_hash FROM articles WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&mut **tx)
+            .await?;
+    Ok(row.map(|(title, content, hash)| StoredVersion {
+        title,
+        content,
+        hash,
+    }))
+}
+
+async fn record_revision(
+    tx: &mut Transaction<'_, Sqlite>,
+    article_id: &str,
+    replaced: &StoredVersion,
+    now: i64,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO article_revisions (article_id, title, content, content_hash, replaced_at)
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(article_id)
+    .bind(&replaced.title)
+    .bind(&replaced.content)
+    .bind(&replaced.hash)
+    .bind(now)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Every stored version of an article, oldest first, ending with the
+/// current one.
+pub async fn list_revisions(pool: &Pool<Sqlite>, article_id: &str) -> Result<Vec<Revision>> {
+    let current: Option<(String, Option<String>)> =
+        sqlx::query_as("SELECT title, content FROM articles WHERE id = $1")
+            .bind(article_id)
+            .fetch_optional(pool)
+            .await?;
+    let (title, content) = current.ok_or_else(|| Error::ArticleNotFound(article_id.to_string()))?;
+    let rows: Vec<(i64, String, Option<String>, i64)> = sqlx::query_as(
+        "SELECT id, title, content, replaced_at FROM article_revisions
+         WHERE article_id = $1 ORDER BY replaced_at ASC, id ASC",
+    )
+    .bind(article_id)
+    .fetch_all(pool)
+    .await?;
+    let mut revisions: Vec<Revision> = rows
+        .into_iter()
+        .map(|(id, title, content, replaced_at)| Revision {
+            id: Some(id),
+            title,
+            content,
+            replaced_at: Some(replaced_at),
+        })
+        .collect();
+    revisions.push(Revision {
+        id: None,
+        title,
+        content,
+        replaced_at: None,
+    });
+    Ok(revisions)
+}
+
+/// Upserts by URL rather than deleting and reinserting, so an enclosure
+/// keeps its id across refreshes.
+async fn replace_enclosures(
+    tx: &mut Transaction<'_, Sqlite>,
+    article_id: &str,
+    enclosures: &[Enclosure],
+) -> Result<()> {
+    for enclosure in enclosures {
+        sqlx::query(
+            "INSERT INTO enclosures (article_id, url, mime_type, length, duration)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT(article_id, url) DO UPDATE SET
+               mime_type = $3, length = $4, duration = $5",
+        )
+        .bind(article_id)
+        .bind(&enclosure.url)
+        .bind(&enclosure.mime_type)
+        .bind(enclosure.length)
+        .bind(enclosure.duration)
+        .execute(&mut **tx)
+        .await?;
+    }
+    let urls: Vec<&str> = enclosures.iter().map(|e| e.url.as_str()).collect();
+    sqlx::query(
+        "DELETE FROM enclosures
+         WHERE article_id = $1 AND url NOT IN (SELECT value FROM json_each($2))",
+    )
+    .bind(article_id)
+    .bind(serde_json::to_string(&urls)?)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredEnclosure {
+    pub id: i64,
+    pub article_id: String,
+    pub url: String,
+    pub mime_type: Option<String>,
+    pub length: Option<i64>,
+    pub duration: Option<i64>,
+}
+
+/// An article with attached media, with its podcast metadata.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpisodeRow {
+    pub article_id: String,
+    pub feed_url: String,
+    pub title: String,
+    pub published_ts: Option<i64>,
+    pub episode: Option<i64>,
+    pub season: Option<i64>,
+    pub image: Option<String>,
+    pub explicit: Option<bool>,
+    pub enclosures: Vec<StoredEnclosure>,
+}
+
+type EpisodeFields = (
+    String,
+    String,
+    String,
+    Option<i64>,
+    Option<i64>,
+    Option<i64>,
+    Option<String>,
+    Option<bool>,
+);
+
+/// Articles with enclosures, newest first, optionally limited to one feed
+/// or one article.
+pub async fn list_episodes(
+    pool: &Pool<Sqlite>,
+    feed_url: Option<&str>,
+    article_id: Option<&str>,
+) -> Result<Vec<EpisodeRow>> {
+    let rows: Vec<EpisodeFields> = sqlx::query_as(
+        "SELECT id, feed_url, title, published_ts, episode, season, episode_image, explicit
+         FROM articles
+         WHERE ($1 IS NULL OR feed_url = $1) AND ($2 IS NULL OR id = $2)
+           AND EXISTS (SELECT 1 FROM enclosures WHERE article_id = articles.id)
+         ORDER BY published_ts DESC, fetched_at DESC",
+    )
+    .bind(feed_url)
+    .bind(article_id)
+    .fetch_all(pool)
+    .await?;
+    let enclosures: Vec<StoredEnclosure> = sqlx::query_as(
+        "SELECT e.id, e.article_id, e.url, e.mime_type, e.length, e.duration
+         FROM enclosures e JOIN articles a ON a.id = e.article_id
+         WHERE ($1 IS NULL OR a.feed_url = $1) AND ($2 IS NULL OR a.id = $2)
+         ORDER BY e.id ASC",
+    )
+    .bind(feed_url)
+    .bind(article_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(
+            |(article_id, feed_url, title, published_ts, episode, season, image, explicit)| {
+                EpisodeRow {
+                    enclosures: enclosures
+                        .iter()
+                        .filter(|e| e.article_id == article_id)
+                        .cloned()
+                        .collect(),
+                    article_id,
+                    feed_url,
+                    title,
+                    published_ts,
+                    episode,
+                    season,
+                    image,
+                    explicit,
+                }
+            },
+        )
+        .collect())
+}
+
+/// Adds a subscription, leaving an existing one with the same URL alone.
+pub async fn insert_feed(pool: &Pool<Sqlite>, url: &str, name: &str) -> Result<()> {
+    sqlx::query("INSERT OR IGNORE INTO feeds (url, name, added_at) VALUES ($1, $2, $3)")
+        .bind(url)
+        .bind(name)
+        .bind(now_ms())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn set_meta(pool: &Pool<Sqlite>, key: &str, value: Option<&str>) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO meta (key, value) VALUES ($1, $2)
+         ON CONFLICT(key) DO UPDATE SET value = $2",
+    )
+    .bind(key)
+    .bind(value)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_enclosure(pool: &Pool<Sqlite>, id: i64) -> Result<StoredEnclosure> {
+    sqlx::query_as(
+        "SELECT id, article_id, url, mime_type, length, duration FROM enclosures WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(Error::EnclosureNotFound(id))
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadRow {
+    pub enclosure_id: i64,
+    pub article_id: String,
+    pub feed_url: String,
+    pub title: String,
+    pub url: String,
+    pub mime_type: Option<String>,
+    pub status: String,
+    pub path: Option<String>,
+    pub bytes_downloaded: i64,
+    pub total_bytes: Option<i64>,
+    pub error: Option<String>,
+    pub auto: bool,
+    pub requested_at: i64,
+    pub completed_at: Option<i64>,
+    pub played_at: Option<i64>,
+}
+
+pub async fn list_downloads(pool: &Pool<Sqlite>) -> Result<Vec<DownloadRow>> {
+    let rows = sqlx::query_as(
+        "SELECT d.enclosure_id, e.article_id, a.feed_url, a.title, e.url, e.mime_type,
+           d.status, d.path, d.bytes_downloaded, d.total_bytes, d.error, d.auto,
+           d.requested_at, d.completed_at, d.played_at
+         FROM downloads d
+         JOIN enclosures e ON e.id = d.enclosure_id
+         JOIN articles a ON a.id = e.article_id
+         ORDER BY d.requested_at DESC",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+pub async fn get_download(pool: &Pool<Sqlite>, enclosure_id: i64) -> Result<Option<DownloadRow>> {
+    Ok(sqlx::query_as(
+        "SELECT d.enclosure_id, e.article_id, a.feed_url, a.title, e.url, e.mime_type,
+           d.status, d.path, d.bytes_downloaded, d.total_bytes, d.error, d.auto,
+           d.requested_at, d.completed_at, d.played_at
+         FROM downloads d
+         JOIN enclosures e ON e.id = d.enclosure_id
+         JOIN articles a ON a.id = e.article_id
+         WHERE d.enclosure_id = $1",
+    )
+    .bind(enclosure_id)
+    .fetch_optional(pool)
+    .await?)
+}
+
+/// Queues a download unless it is already stored, retrying a failed one.
+/// Asking by hand takes a download out of its feed rule's hands. Returns
+/// whether there is anything left to fetch.
+pub async fn queue_download(pool: &Pool<Sqlite>, enclosure_id: i64, auto: bool) -> Result<bool> {
+    get_enclosure(pool, enclosure_id).await?;
+    sqlx::query(
+        "INSERT INTO downloads (enclosure_id, status, auto, requested_at)
+         VALUES ($1, 'queued', $2, $3)
+         ON CONFLICT(enclosure_id) DO UPDATE SET
+           auto = downloads.auto AND $2,
+           status = CASE WHEN downloads.status = 'failed' THEN 'queued' ELSE downloads.status END,
+           error = NULL",
+    )
+    .bind(enclosure_id)
+    .bind(auto)
+    .bind(now_ms())
+    .execute(pool)
+    .await?;
+    let status: String = sqlx::query_scalar("SELECT status FROM downloads WHERE enclosure_id = $1")
+        .bind(enclosure_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(status != "done")
+}
+
+pub async fn set_download_progress(pool: &Pool<Sqlite>, progress: &Progress) -> Result<()> {
+    sqlx::query(
+        "UPDATE downloads SET status = $2, bytes_downloaded = $3, total_bytes = $4, error = NULL
+         WHERE enclosure_id = $1",
+    )
+    .bind(progress.enclosure_id)
+    .bind(progress.status.as_str())
+    .bind(progress.downloaded)
+    .bind(progress.total)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn finish_download(
+    pool: &Pool<Sqlite>,
+    enclosure_id: i64,
+    path: &str,
+    bytes: i64,
+) -> Result<()> {
+    sqlx::query(
+        "UPDATE downloads SET status = 'done', path = $2, bytes_downloaded = $3, total_bytes = $3,
+           completed_at = $4
+         WHERE enclosure_id = $1",
+    )
+    .bind(enclosure_id)
+    .bind(path)
+    .bind(bytes)
+    .bind(now_ms())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn fail_download(pool: &Pool<Sqlite>, enclosure_id: i64, error: &str) -> Result<()> {
+    sqlx::query("UPDATE downloads SET status = 'failed', error = $2 WHERE enclosure_id = $1")
+        .bind(enclosure_id)
+        .bind(error)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_download(pool: &Pool<Sqlite>, enclosure_id: i64) -> Result<()> {
+    sqlx::query("DELETE FROM downloads WHERE enclosure_id = $1")
+        .bind(enclosure_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn mark_played(pool: &Pool<Sqlite>, enclosure_id: i64) -> Result<()> {
+    sqlx::query("UPDATE downloads SET played_at = $2 WHERE enclosure_id = $1")
+        .bind(enclosure_id)
+        .bind(now_ms())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Sets how many of a feed's newest episodes to keep downloaded; `None`
+/// turns automatic downloads off.
+pub async fn set_auto_download(pool: &Pool<Sqlite>, url: &str, keep: Option<i64>) -> Result<()> {
+    let result = sqlx::query("UPDATE feeds SET auto_download_keep = $2 WHERE url = $1")
+        .bind(url)
+        .bind(keep)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(Error::FeedNotFound(url.to_string()));
+    }
+    Ok(())
+}
+
+pub async fn list_auto_download_rules(pool: &Pool<Sqlite>) -> Result<Vec<(String, i64)>> {
+    let rules = sqlx::query_as(
+        "SELECT url, auto_download_keep FROM feeds
+         WHERE auto_download_keep > 0 AND retired_at IS NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rules)
+}
+
+/// The first enclosure of each of the feed's newest `limit` episodes.
+pub async fn latest_enclosures(
+    pool: &Pool<Sqlite>,
+    feed_url: &str,
+    limit: i64,
+) -> Result<Vec<i64>> {
+    let ids = sqlx::query_scalar(
+        "SELECT (SELECT MIN(e.id) FROM enclosures e WHERE e.article_id = a.id)
+         FROM articles a
+         WHERE a.feed_url = $1 AND EXISTS (SELECT 1 FROM enclosures WHERE article_id = a.id)
+         ORDER BY a.published_ts DESC, a.fetched_at DESC
+         LIMIT $2",
+    )
+    .bind(feed_url)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(ids)
+}
+
+pub async fn auto_downloads_for_feed(pool: &Pool<Sqlite>, feed_url: &str) -> Result<Vec<i64>> {
+    let ids = sqlx::query_scalar(
+        "SELECT d.enclosure_id FROM downloads d
+         JOIN enclosures e ON e.id = d.enclosure_id
+         JOIN articles a ON a.id = e.article_id
+         WHERE a.feed_url = $1 AND d.auto = 1",
+    )
+    .bind(feed_url)
+    .fetch_all(pool)
+    .await?;
+    Ok(ids)
+}
+
+/// Looks up a cached image's content type, marking it as just used.
+pub async fn touch_cached_image(pool: &Pool<Sqlite>, url: &str) -> Result<Option<String>> {
+    Ok(sqlx::query_scalar(
+        "UPDATE image_cache SET last_used_at = $2 WHERE url = $1 RETURNING content_type",
+    )
+    .bind(url)
+    .bind(now_ms())
+    .fetch_optional(pool)
+    .await?)
+}
+
+pub async fn insert_cached_image(
+    pool: &Pool<Sqlite>,
+    url: &str,
+    file: &str,
+    content_type: &str,
+    size: i64,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO image_cache (url, file, content_type, size, last_used_at)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT(url) DO UPDATE SET
+           file = $2, content_type = $3, size = $4, last_used_at = $5",
+    )
+    .bind(url)
+    .bind(file)
+    .bind(content_type)
+    .bind(size)
+    .bind(now_ms())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn forget_cached_image(pool: &Pool<Sqlite>, url: &str) -> Result<()> {
+    sqlx::query("DELETE FROM image_cache WHERE url = $1")
+        .bind(url)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Forgets the least recently used images beyond `limit` bytes and returns
+/// their URLs for the caller to delete the files of.
+pub async fn evict_cached_images(pool: &Pool<Sqlite>, limit: i64) -> Result<Vec<String>> {
+    let rows: Vec<(String, i64)> =
+        sqlx::query_as("SELECT url, size FROM image_cache ORDER BY last_used_at DESC")
+            .fetch_all(pool)
+            .await?;
+    let mut total = 0;
+    let mut evicted = Vec::new();
+    for (url, size) in rows {
+        total += size;
+        if total > limit {
+            forget_cached_image(pool, &url).await?;
+            evicted.push(url);
+        }
+    }
+    Ok(evicted)
+}
+
+/// The columns ingest rewrites, with `fetched_at` to notice a refresh
+/// storing a newer version in the meantime.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StoredContent {
+    pub id: String,
+    pub link: Option<String>,
+    pub content: Option<String>,
+    pub full_content: Option<String>,
+    pub lead_image: Option<String>,
+    pub episode_image: Option<String>,
+    pub fetched_at: i64,
+}
+
+pub async fn list_stored_content(pool: &Pool<Sqlite>) -> Result<Vec<StoredContent>> {
+    Ok(sqlx::query_as(
+        "SELECT id, link, content, full_content, lead_image, episode_image, fetched_at FROM articles",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn set_stored_content(pool: &Pool<Sqlite>, stored: &StoredContent) -> Result<()> {
+    sqlx::query(
+        "UPDATE articles SET content = $2, lead_image = $3, episode_image = $4, full_content = $6
+         WHERE id = $1 AND fetched_at = $5",
+    )
+    .bind(&stored.id)
+    .bind(&stored.content)
+    .bind(&stored.lead_image)
+    .bind(&stored.episode_image)
+    .bind(stored.fetched_at)
+    .bind(&stored.full_content)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Where to look for a feed's icon.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct IconSource {
+    pub url: String,
+    pub site_url: Option<String>,
+    pub icon_url: Option<String>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FeedIcon {
+    pub source_url: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Remembers the site and icon a feed points to, for icon lookups.
+pub async fn update_feed_links(
+    pool: &Pool<Sqlite>,
+    url: &str,
+    site_url: Option<&str>,
+    icon_url: Option<&str>,
+) -> Result<()> {
+    sqlx::query("UPDATE feeds SET site_url = $2, icon_url = $3 WHERE url = $1")
+        .bind(url)
+        .bind(site_url)
+        .bind(icon_url)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Feeds without an icon row, with an icon found before `found_before`, or
+/// with none found as of `missing_before`.
+pub async fn list_stale_icons(
+    pool: &Pool<Sqlite>,
+    found_before: i64,
+    missing_before: i64,
+) -> Result<Vec<IconSource>> {
+    Ok(sqlx::query_as(
+        "SELECT f.url, f.site_url, f.icon_url
+         FROM feeds f LEFT JOIN feed_icons i ON i.feed_url = f.url
+         WHERE f.retired_at IS NULL
+           AND f.url NOT LIKE 'mailto:%'
+           AND (i.feed_url IS NULL
+             OR (i.data IS NOT NULL AND i.fetched_at < $1)
+             OR (i.data IS NULL AND i.fetched_at < $2))",
+    )
+    .bind(found_before)
+    .bind(missing_before)
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn save_feed_icon(pool: &Pool<Sqlite>, feed_url: &str, icon: &FeedIcon) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO feed_icons (feed_url, source_url, content_type, data, fetched_at)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT(feed_url) DO UPDATE SET
+           source_url = $2, content_type = $3, data = $4, fetched_at = $5",
+    )
+    .bind(feed_url)
+    .bind(&icon.source_url)
+    .bind(&icon.content_type)
+    .bind(&icon.data)
+    .bind(now_ms())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Records a lookup that found nothing, keeping any icon found earlier.
+pub async fn mark_icon_checked(pool: &Pool<Sqlite>, feed_url: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO feed_icons (feed_url, fetched_at) VALUES ($1, $2)
+         ON CONFLICT(feed_url) DO UPDATE SET fetched_at = $2",
+    )
+    .bind(feed_url)
+    .bind(now_ms())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_feed_icon(pool: &Pool<Sqlite>, feed_url: &str) -> Result<Option<FeedIcon>> {
+    Ok(sqlx::query_as(
+        "SELECT source_url, content_type, data FROM feed_icons
+         WHERE feed_url = $1 AND data IS NOT NULL",
+    )
+    .bind(feed_url)
+    .fetch_optional(pool)
+    .await?)
+}
+
+/// Feeds that have an icon, with when it was fetched.
+pub async fn list_feed_icons(pool: &Pool<Sqlite>) -> Result<Vec<(String, i64)>> {
+    Ok(
+        sqlx::query_as("SELECT feed_url, fetched_at FROM feed_icons WHERE data IS NOT NULL")
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+/// Turns full-text extraction on or off for a feed.
+pub async fn set_full_text(pool: &Pool<Sqlite>, url: &str, enabled: bool) -> Result<()> {
+    let result = sqlx::query("UPDATE feeds SET full_text = $2 WHERE url = $1")
+        .bind(url)
+        .bind(enabled)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(Error::FeedNotFound(url.to_string()));
+    }
+    Ok(())
+}
+
+/// Newest articles from full-text feeds that haven't been extracted or
+/// failed yet, as (id, link).
+pub async fn list_pending_full_text(
+    pool: &Pool<Sqlite>,
+    limit: i64,
+) -> Result<Vec<(String, String)>> {
+    Ok(sqlx::query_as(
+        "SELECT a.id, a.link FROM articles a JOIN feeds f ON f.url = a.feed_url
+         WHERE f.full_text = 1 AND f.retired_at IS NULL
+           AND a.link IS NOT NULL AND a.full_content_at IS NULL
+         ORDER BY a.published_ts DESC, a.fetched_at DESC
+         LIMIT $1",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn article_link(pool: &Pool<Sqlite>, article_id: &str) -> Result<Option<String>> {
+    let link: Option<(Option<String>,)> = sqlx::query_as("SELECT link FROM articles WHERE id = $1")
+        .bind(article_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(link.and_then(|(link,)| link))
+}
+
+/// Stores an extraction: the content on success, the error message on
+/// failure. Either way the article isn't picked up again on its own.
+pub async fn set_full_content(
+    pool: &Pool<Sqlite>,
+    article_id: &str,
+    result: &Result<String>,
+) -> Result<()> {
+    let (content, error) = match result {
+        Ok(content) => (Some(content.as_str()), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    sqlx::query(
+        "UPDATE articles SET full_content = $2, full_content_error = $3, full_content_at = $4
+         WHERE id = $1",
+    )
+    .bind(article_id)
+    .bind(content)
+    .bind(error)
+    .bind(now_ms())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The selectors a feed is scraped with, if it's an HTML page rather than
+/// a real feed.
+pub async fn get_scraper_selectors(pool: &Pool<Sqlite>, url: &str) -> Result<Option<Selectors>> {
+    Ok(sqlx::query_as(
+        "SELECT item_selector, title_selector, link_selector, date_selector, content_selector
+         FROM scraper_feeds WHERE feed_url = $1",
+    )
+    .bind(url)
+    .fetch_optional(pool)
+    .await?)
+}
+
+pub async fn save_scraper_selectors(
+    pool: &Pool<Sqlite>,
+    url: &str,
+    selectors: &Selectors,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO scraper_feeds
+           (feed_url, item_selector, title_selector, link_selector, date_selector, content_selector)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         ON CONFLICT(feed_url) DO UPDATE SET
+           item_selector = $2, title_selector = $3, link_selector = $4,
+           date_selector = $5, content_selector = $6",
+    )
+    .bind(url)
+    .bind(&selectors.item)
+    .bind(&selectors.title)
+    .bind(&selectors.link)
+    .bind(&selectors.date)
+    .bind(&selectors.content)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Mailbox {
+    pub path: String,
+    pub kind: MailboxKind,
+    /// The mailbox's modification time at the last import.
+    pub modified_at: Option<i64>,
+    /// Why the last import failed, if it did.
+    pub last_error: Option<String>,
+}
+
+pub async fn insert_mailbox(pool: &Pool<Sqlite>, path: &str, kind: MailboxKind) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO mailboxes (path, kind, added_at) VALUES ($1, $2, $3)
+         ON CONFLICT(path) DO UPDATE SET kind = $2",
+    )
+    .bind(path)
+    .bind(kind)
+    .bind(now_ms())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_mailbox(pool: &Pool<Sqlite>, path: &str) -> Result<()> {
+    sqlx::query("DELETE FROM mailboxes WHERE path = $1")
+        .bind(path)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn list_mailboxes(pool: &Pool<Sqlite>) -> Result<Vec<Mailbox>> {
+    Ok(sqlx::query_as(
+        "SELECT path, kind, modified_at, last_error FROM mailboxes ORDER BY added_at ASC",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn mark_mailbox_synced(
+    pool: &Pool<Sqlite>,
+    path: &str,
+    modified_at: Option<i64>,
+) -> Result<()> {
+    sqlx::query("UPDATE mailboxes SET modified_at = $2, last_error = NULL WHERE path = $1")
+        .bind(path)
+        .bind(modified_at)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn record_mailbox_failure(pool: &Pool<Sqlite>, path: &str, error: &str) -> Result<()> {
+    sqlx::query("UPDATE mailboxes SET last_error = $2 WHERE path = $1")
+        .bind(path)
+        .bind(error)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Message-IDs already imported, from any mailbox.
+pub async fn consumed_message_ids(pool: &Pool<Sqlite>) -> Result<HashSet<String>> {
+    let ids: Vec<(String,)> = sqlx::query_as("SELECT message_id FROM mail_messages")
+        .fetch_all(pool)
+        .await?;
+    Ok(ids.into_iter().map(|(id,)| id).collect())
+}
+
+/// Stores one sender's newsletters as a feed and marks their Message-IDs
+/// consumed, together, so a failure leaves neither and the next sync
+/// imports them again. Returns how many articles were new.
+pub async fn store_newsletters(
+    pool: &Pool<Sqlite>,
+    feed_url: &str,
+    feed_name: &str,
+    items: &[Item],
+    message_ids: &[&str],
+) -> Result<usize> {
+    let now = now_ms();
+    let mut tx = pool.begin().await?;
+    sqlx::query("INSERT OR IGNORE INTO feeds (url, name, added_at) VALUES ($1, $2, $3)")
+        .bind(feed_url)
+        .bind(feed_name)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+    let inserted = store_articles(&mut tx, feed_url, feed_name, items).await?;
+    for id in message_ids {
+        sqlx::query(
+            "INSERT OR IGNORE INTO mail_messages (message_id, feed_url, consumed_at)
+             VALUES ($1, $2, $3)",
+        )
+        .bind(id)
+        .bind(feed_url)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(inserted)
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use sqlx::{Pool, Sqlite};
+use url::Url;
+
+use crate::db;
+use crate::error::{Error, Result};
+use crate::refresh::{self, RefreshEngine};
+use crate::scheduler::{self, ArticlesUpdated};
+
+/// The watched directory; every feed file directly inside it is a
+/// subscription.
+const DIRECTORY_KEY: &str = "feed_directory";
+const FEED_EXTENSIONS: &[&str] = &["atom", "json", "rdf", "rss", "xml"];
+
+pub async fn set_directory(pool: &Pool<Sqlite>, path: Option<&str>) -> Result<()> {
+    let Some(path) = path.map(str::trim).filter(|p| !p.is_empty()) else {
+        return db::set_meta(pool, DIRECTORY_KEY, None).await;
+    };
+    let read_error = |e| Error::FeedFile(path.to_string(), e);
+    // Stored canonical, so it compares equal to the parent of the files
+    // found in it.
+    let directory = tokio::fs::canonicalize(path).await.map_err(read_error)?;
+    if !tokio::fs::metadata(&directory)
+        .await
+        .map_err(read_error)?
+        .is_dir()
+    {
+        return Err(read_error(std::io::ErrorKind::NotADirectory.into()));
+    }
+    let directory = directory
+        .to_str()
+        .ok_or_else(|| Error::InvalidUrl(path.to_string()))?;
+    db::set_meta(pool, DIRECTORY_KEY, Some(directory)).await
+}
+
+/// Brings subscriptions in line with the watched directory: new feed files
+/// are subscribed to, unless the user unsubscribed from them, removed ones
+/// retired and returning ones restored.
+/// Then every file feed there is refreshed, which only parses files that
+/// changed since the last pass.
+pub async fn sync(pool: &Pool<Sqlite>, engine: &RefreshEngine) -> Result<ArticlesUpdated> {
+    let Some(directory) = db::get_meta(pool, DIRECTORY_KEY).await? else {
+        return Ok(ArticlesUpdated::default());
+    };
+    let files = feed_files(Path::new(&directory)).await?;
+    let mut results = Vec::new();
+
+    let subscribed: HashMap<String, bool> = db::list_file_feeds(pool).await?.into_iter().collect();
+    let skipped: HashMap<String, db::DirectoryFile> = db::list_directory_files(pool)
+        .await?
+        .into_iter()
+        .map(|file| (file.url.clone(), file))
+        .collect();
+    for (url, path) in &files {
+        if subscribed.contains_key(url) {
+            continue;
+        }
+        let modified = tokio::fs::metadata(path)
+            .await
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as i64);
+        if let Some(file) = skipped.get(url) {
+            let unchanged = file.last_error.is_some() && file.modified_at == modified;
+            if file.unsubscribed_at.is_some() || unchanged {
+                continue;
+            }
+        }
+        match refresh::subscribe(pool, url, None, None).await {
+            Ok(outcome) => results.push((url.clone(), Ok(outcome))),
+            Err(e) => {
+                log::warn!("could not subscribe to {}: {e}", path.display());
+                db::record_unreadable_file(pool, url, modified, &e.to_string()).await?;
+            }
+        }
+    }
+
+    let mut present = Vec::new();
+    for (url, retired) in subscribed {
+        if !in_directory(&url, Path::new(&directory)) {
+            continue;
+        }
+        match (files.contains_key(&url), retired) {
+            (true, true) => db::restore_feed(pool, &url).await?,
+            (false, false) => db::retire_feed(pool, &url).await?,
+            _ => {}
+        }
+        if files.contains_key(&url) {
+            present.push(url);
+        }
+    }
+    results.extend(engine.refresh_all(pool, present).await?);
+    Ok(scheduler::summarize(results))
+}
+
+/// Feed files directly inside `directory`, keyed by their `file://` URL.
+async fn feed_files(directory: &Path) -> Result<HashMap<String, PathBuf>> {
+    let read_error = |e| Error::FeedFile(directory.display().to_string(), e);
+    let mut entries = tokio::fs::read_dir(directory).await.map_err(read_error)?;
+    let mut files = HashMap::new();
+    while let Some(entry) = entries.next_entry().await.map_err(read_error)? {
+        let path = entry.path();
+        let is_feed = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| FEED_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+        if !is_feed || !entry.file_type().await.is_ok_and(|t| t.is_file()) {
+            continue;
+        }
+        if let Ok(url) = Url::from_file_path(&path) {
+            files.insert(url.into(), path);
+        }
+    }
+    Ok(files)
+}
+
+fn in_directory(url: &str, directory: &Path) -> bool {
+    Url::parse(url)
+        .ok()
+        .and_then(|u| u.to_file_path().ok())
+        .is_some_and(|path| path.parent() == Some(directory))
+}
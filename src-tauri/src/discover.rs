@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+
+use scraper::{Html, Selector};
+use serde::Serialize;
+use tokio::task::JoinSet;
+use url::Url;
+
+use crate::error::{Error, Result};
+use crate::feed::{self, FeedFormat};
+use crate::fetch;
+
+/// Paths tried on the site root when the page doesn't advertise a feed.
+const COMMON_PATHS: &[&str] = &[
+    "/feed",
+    "/rss",
+    "/feed.xml",
+    "/rss.xml",
+    "/atom.xml",
+    "/index.xml",
+    "/feed.json",
+];
+
+const FEED_TYPES: &[&str] = &[
+    "application/rss+xml",
+    "application/atom+xml",
+    "application/feed+json",
+    "application/json",
+    "application/rdf+xml",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateSource {
+    /// The URL the user entered is itself a feed.
+    Direct,
+    /// Advertised with `<link rel="alternate">` on the page.
+    Page,
+    /// Found by trying a common path.
+    Probe,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedCandidate {
+    pub url: String,
+    pub title: String,
+    pub format: FeedFormat,
+    pub source: CandidateSource,
+    pub item_count: usize,
+}
+
+/// Returns the feeds behind `url`, best match first. Every candidate has been
+/// fetched and parsed, so the titles are the feeds' own.
+pub async fn discover_feeds(url: &str) -> Result<Vec<FeedCandidate>> {
+    let page = fetch::get(url).await?;
+    if let Ok(parsed) = feed::parse_with_content_type(&page.body, page.content_type.as_deref()) {
+        return Ok(vec![FeedCandidate {
+            url: page.url,
+            title: parsed.title,
+            format: parsed.format,
+            source: CandidateSource::Direct,
+            item_count: parsed.items.len(),
+        }]);
+    }
+
+    let base = Url::parse(&page.url).map_err(|_| Error::InvalidUrl(page.url.clone()))?;
+    let mut urls: Vec<(String, CandidateSource)> = advertised_feeds(&page.body, &base)
+        .into_iter()
+        .map(|u| (u, CandidateSource::Page))
+        .collect();
+    for path in COMMON_PATHS {
+        if let Ok(probe) = base.join(path) {
+            urls.push((probe.to_string(), CandidateSource::Probe));
+        }
+    }
+    let mut seen = HashSet::new();
+    urls.retain(|(u, _)| seen.insert(u.clone()));
+
+    let mut probes = JoinSet::new();
+    for (order, (url, source)) in urls.into_iter().enumerate() {
+        probes.spawn(async move { (order, validate(url, source).await) });
+    }
+    let mut found = Vec::new();
+    while let Some(joined) = probes.join_next().await {
+        if let Ok((order, Some(candidate))) = joined {
+            found.push((order, candidate));
+        }
+    }
+
+    // A site often answers several probe paths with the same feed.
+    found.sort_by_key(|(order, c)| (rank(c), *order));
+    let mut titles = HashSet::new();
+    Ok(found
+        .into_iter()
+        .map(|(_, c)| c)
+        .filter(|c| titles.insert(c.title.clone()) || c.source == CandidateSource::Page)
+        .collect())
+}
+
+fn advertised_feeds(html: &str, page_url: &Url) -> Vec<String> {
+    let doc = Html::parse_document(html);
+    let base = Selector::parse("base[href]")
+        .ok()
+        .and_then(|sel| doc.select(&sel).next())
+        .and_then(|el| el.value().attr("href"))
+        .and_then(|href| page_url.join(href).ok())
+        .unwrap_or_else(|| page_url.clone());
+
+    let Ok(links) = Selector::parse("link[rel][href]") else {
+        return Vec::new();
+    };
+    doc.select(&links)
+        .filter(|el| {
+            let rel = el.value().attr("rel").unwrap_or_default();
+            let ty = el.value().attr("type").unwrap_or_default();
+            rel.split_ascii_whitespace()
+                .any(|r| r.eq_ignore_ascii_case("alternate"))
+                && FEED_TYPES.iter().any(|t| ty.trim().eq_ignore_ascii_case(t))
+        })
+        .filter_map(|el| el.value().attr("href"))
+        .filter_map(|href| base.join(href.trim()).ok())
+        .map(|u| u.to_string())
+        .collect()
+}
+
+async fn validate(url: String, source: CandidateSource) -> Option<FeedCandidate> {
+    let page = fetch::get(&url).await.ok()?;
+    let parsed = feed::parse_with_content_type(&page.body, page.content_type.as_deref()).ok()?;
+    Some(FeedCandidate {
+        url: page.url,
+        title: parsed.title,
+        format: parsed.format,
+        source,
+        item_count: parsed.items.len(),
+    })
+}
+
+/// Lower is better: advertised feeds before probed ones, then by format,
+/// with comment feeds pushed to the end.
+fn rank(c: &FeedCandidate) -> (bool, u8, u8) {
+    let comments = c.url.to_ascii_lowercase().contains("comments")
+        || c.title.to_ascii_lowercase().contains("comments");
+    let source = match c.source {
+        CandidateSource::Direct => 0,
+        CandidateSource::Page => 1,
+        CandidateSource::Probe => 2,
+    };
+    let format = match c.format {
+        FeedFormat::Atom => 0,
+        FeedFormat::Rss2 => 1,
+        FeedFormat::JsonFeed => 2,
+        FeedFormat::Rss1 => 3,
+        FeedFormat::Rss09 => 4,
+        FeedFormat::Html => 5,
+    };
+    (comments, source, format)
+}
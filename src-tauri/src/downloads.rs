@@ -0,0 +1,464 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_http::reqwest::header::CONTENT_RANGE;
+use tauri_plugin_http::reqwest::{Response, StatusCode};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Semaphore;
+use tokio::task::AbortHandle;
+use tokio::time::Instant;
+
+use crate::db::{self, DownloadRow};
+use crate::error::{Error, Result};
+use crate::fetch;
+
+/// Emitted as downloads start, progress, finish or fail.
+pub const DOWNLOAD_PROGRESS: &str = "download-progress";
+
+const QUOTA_KEY: &str = "download_quota_bytes";
+const DEFAULT_QUOTA: i64 = 2 * 1024 * 1024 * 1024;
+const MAX_CONCURRENT: usize = 2;
+/// How often a running download reports progress and saves it.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+const DIR: &str = "podcasts";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadStatus {
+    Queued,
+    Downloading,
+    Done,
+    Failed,
+}
+
+impl DownloadStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DownloadStatus::Queued => "queued",
+            DownloadStatus::Downloading => "downloading",
+            DownloadStatus::Done => "done",
+            DownloadStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Progress {
+    pub enclosure_id: i64,
+    pub status: DownloadStatus,
+    pub downloaded: i64,
+    pub total: Option<i64>,
+    pub error: Option<String>,
+}
+
+/// Downloads enclosures into the app data directory, a couple at a time.
+/// Partial files are kept so a transfer interrupted by a failure or a
+/// restart picks up where it stopped.
+#[derive(Clone)]
+pub struct DownloadManager {
+    permits: Arc<Semaphore>,
+    active: Arc<Mutex<HashMap<i64, AbortHandle>>>,
+}
+
+impl Default for DownloadManager {
+    fn default() -> Self {
+        DownloadManager {
+            permits: Arc::new(Semaphore::new(MAX_CONCURRENT)),
+            active: Arc::default(),
+        }
+    }
+}
+
+impl DownloadManager {
+    /// Queues an enclosure; `auto` marks downloads made by a feed's rule,
+    /// which the rule may delete again.
+    pub async fn enqueue<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        enclosure_id: i64,
+        auto: bool,
+    ) -> Result<()> {
+        let pool = db::pool(app).await?;
+        if db::queue_download(&pool, enclosure_id, auto).await? {
+            let (downloaded, total) = partial(app, &pool, enclosure_id).await;
+            let _ = app.emit(
+                DOWNLOAD_PROGRESS,
+                Progress {
+                    enclosure_id,
+                    status: DownloadStatus::Queued,
+                    downloaded,
+                    total,
+                    error: None,
+                },
+            );
+            self.spawn(app.clone(), enclosure_id);
+        }
+        Ok(())
+    }
+
+    /// Cancels a running download and removes whatever was stored for it.
+    pub async fn delete<R: Runtime>(&self, app: &AppHandle<R>, enclosure_id: i64) -> Result<()> {
+        if let Some(task) = self.active.lock().unwrap().remove(&enclosure_id) {
+            task.abort();
+        }
+        let pool = db::pool(app).await?;
+        db::delete_download(&pool, enclosure_id).await?;
+        remove_files(app, enclosure_id).await;
+        Ok(())
+    }
+
+    fn spawn<R: Runtime>(&self, app: AppHandle<R>, enclosure_id: i64) {
+        let mut active = self.active.lock().unwrap();
+        if active.contains_key(&enclosure_id) {
+            return;
+        }
+        let manager = self.clone();
+        let task = tauri::async_runtime::spawn(async move {
+            let _permit = manager.permits.acquire().await;
+            if let Err(e) = run(&app, enclosure_id).await {
+                report_failure(&app, enclosure_id, &e).await;
+            }
+            manager.active.lock().unwrap().remove(&enclosure_id);
+        });
+        active.insert(enclosure_id, task.inner().abort_handle());
+    }
+}
+
+/// Resumes downloads left unfinished by the last session, then applies the
+/// auto-download rules and quota.
+pub fn start<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = resume(&app).await {
+            log::warn!("could not resume downloads: {e}");
+        }
+        if let Err(e) = apply_rules(&app).await {
+            log::warn!("could not apply download rules: {e}");
+        }
+    });
+}
+
+async fn resume<R: Runtime>(app: &AppHandle<R>) -> Result<()> {
+    let pool = db::pool(app).await?;
+    let manager = app.state::<DownloadManager>();
+    for row in db::list_downloads(&pool).await? {
+        if matches!(row.status.as_str(), "queued" | "downloading") {
+            manager.spawn(app.clone(), row.enclosure_id);
+        }
+    }
+    remove_orphans(app, &pool).await
+}
+
+/// For every feed with a "keep latest N" rule, downloads the newest N
+/// episodes and deletes older downloads the rule made.
+pub async fn apply_rules<R: Runtime>(app: &AppHandle<R>) -> Result<()> {
+    let pool = db::pool(app).await?;
+    let manager = app.state::<DownloadManager>();
+    for (feed_url, keep) in db::list_auto_download_rules(&pool).await? {
+        let wanted = db::latest_enclosures(&pool, &feed_url, keep).await?;
+        for enclosure_id in &wanted {
+            manager.enqueue(app, *enclosure_id, true).await?;
+        }
+        for enclosure_id in db::auto_downloads_for_feed(&pool, &feed_url).await? {
+            if !wanted.contains(&enclosure_id) {
+                manager.delete(app, enclosure_id).await?;
+            }
+        }
+    }
+    enforce_quota(app, &pool).await
+}
+
+/// Sets the disk quota in bytes; `None` restores the default.
+pub async fn set_quota<R: Runtime>(app: &AppHandle<R>, bytes: Option<i64>) -> Result<()> {
+    let pool = db::pool(app).await?;
+    let value = bytes.filter(|b| *b > 0).map(|b| b.to_string());
+    db::set_meta(&pool, QUOTA_KEY, value.as_deref()).await?;
+    enforce_quota(app, &pool).await
+}
+
+async fn run<R: Runtime>(app: &AppHandle<R>, enclosure_id: i64) -> Result<()> {
+    let pool = db::pool(app).await?;
+    let enclosure = db::get_enclosure(&pool, enclosure_id).await?;
+    let dir = downloads_dir(app)?;
+    tokio::fs::create_dir_all(&dir).await?;
+    let part = part_path(&dir, enclosure_id);
+    let path = dir.join(format!(
+        "{enclosure_id}.{}",
+        extension(&enclosure.url, enclosure.mime_type.as_deref())
+    ));
+
+    let mut downloaded = tokio::fs::metadata(&part)
+        .await
+        .map(|m| m.len() as i64)
+        .unwrap_or(0);
+    let (_, known_total) = partial(app, &pool, enclosure_id).await;
+    let mut resp = fetch::download(&enclosure.url, downloaded as u64).await?;
+    let mut resume = check_resume(&resp, downloaded, known_total)?;
+    if matches!(resume, Resume::Restart) {
+        downloaded = 0;
+        resp = fetch::download(&enclosure.url, 0).await?;
+        resume = check_resume(&resp, 0, None)?;
+    }
+    let total = match resume {
+        Resume::Append(total) => total,
+        Resume::Replace(total) => {
+            downloaded = 0;
+            total
+        }
+        Resume::Complete => Some(downloaded),
+        Resume::Restart => return Err(Error::Status(resp.status().as_u16())),
+    };
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(downloaded > 0)
+        .truncate(downloaded == 0)
+        .open(&part)
+        .await?;
+    let mut progress = Progress {
+        enclosure_id,
+        status: DownloadStatus::Downloading,
+        downloaded,
+        total,
+        error: None,
+    };
+    db::set_download_progress(&pool, &progress).await?;
+    let _ = app.emit(DOWNLOAD_PROGRESS, &progress);
+
+    let mut reported_at = Instant::now();
+    if total != Some(downloaded) {
+        while let Some(chunk) = resp.chunk().await? {
+            file.write_all(&chunk).await?;
+            progress.downloaded += chunk.len() as i64;
+            if reported_at.elapsed() >= PROGRESS_INTERVAL {
+                reported_at = Instant::now();
+                db::set_download_progress(&pool, &progress).await?;
+                let _ = app.emit(DOWNLOAD_PROGRESS, &progress);
+            }
+        }
+    }
+    file.flush().await?;
+    drop(file);
+
+    tokio::fs::rename(&part, &path).await?;
+    progress.status = DownloadStatus::Done;
+    progress.total = Some(progress.downloaded);
+    db::finish_download(
+        &pool,
+        enclosure_id,
+        &path.to_string_lossy(),
+        progress.downloaded,
+    )
+    .await?;
+    let _ = app.emit(DOWNLOAD_PROGRESS, &progress);
+    enforce_quota(app, &pool).await
+}
+
+/// How a response continues the partial file on disk.
+enum Resume {
+    /// The bytes after the partial file, with the full size if known.
+    Append(Option<i64>),
+    /// The whole file from the start.
+    Replace(Option<i64>),
+    /// The partial file is already the whole file.
+    Complete,
+    /// Nothing usable; ask again without a range.
+    Restart,
+}
+
+fn check_resume(resp: &Response, downloaded: i64, known_total: Option<i64>) -> Result<Resume> {
+    let range = resp
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(content_range);
+    Ok(match resp.status() {
+        // Appending anything but the bytes we asked for corrupts the file.
+        StatusCode::PARTIAL_CONTENT => match range {
+            Some((Some(first), size)) if first == downloaded => Resume::Append(
+                size.or_else(|| resp.content_length().map(|len| downloaded + len as i64)),
+            ),
+            _ => Resume::Restart,
+        },
+        // The server ignored the range, or there was none to send.
+        status if status.is_success() => {
+            Resume::Replace(resp.content_length().map(|len| len as i64))
+        }
+        StatusCode::RANGE_NOT_SATISFIABLE => {
+            let size = range.and_then(|(_, size)| size).or(known_total);
+            if downloaded > 0 && size == Some(downloaded) {
+                Resume::Complete
+            } else {
+                Resume::Restart
+            }
+        }
+        status => return Err(Error::Status(status.as_u16())),
+    })
+}
+
+/// Reads `Content-Range: bytes first-last/size` (or `bytes */size` on a
+/// 416) as the first byte and the full size, either of which may be unknown.
+fn content_range(value: &str) -> Option<(Option<i64>, Option<i64>)> {
+    let (range, size) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let first = range
+        .split_once('-')
+        .and_then(|(first, _)| first.trim().parse().ok());
+    Some((first, size.trim().parse().ok()))
+}
+
+async fn report_failure<R: Runtime>(app: &AppHandle<R>, enclosure_id: i64, error: &Error) {
+    let message = error.to_string();
+    let mut progress = Progress {
+        enclosure_id,
+        status: DownloadStatus::Failed,
+        downloaded: 0,
+        total: None,
+        error: Some(message.clone()),
+    };
+    if let Ok(pool) = db::pool(app).await {
+        (progress.downloaded, progress.total) = partial(app, &pool, enclosure_id).await;
+        if let Err(e) = db::fail_download(&pool, enclosure_id, &message).await {
+            log::warn!("could not record failed download {enclosure_id}: {e}");
+        }
+    }
+    let _ = app.emit(DOWNLOAD_PROGRESS, progress);
+}
+
+/// How much of an unfinished download is on disk, with the full size a
+/// previous response gave.
+async fn partial<R: Runtime>(
+    app: &AppHandle<R>,
+    pool: &Pool<Sqlite>,
+    enclosure_id: i64,
+) -> (i64, Option<i64>) {
+    let downloaded = match downloads_dir(app) {
+        Ok(dir) => tokio::fs::metadata(part_path(&dir, enclosure_id))
+            .await
+            .map_or(0, |m| m.len() as i64),
+        Err(_) => 0,
+    };
+    let total = db::get_download(pool, enclosure_id)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|row| row.total_bytes);
+    (downloaded, total)
+}
+
+/// Deletes finished downloads until they fit the quota: played episodes
+/// first, then the oldest ones a rule downloaded. Unplayed episodes the user
+/// asked for are never evicted.
+async fn enforce_quota<R: Runtime>(app: &AppHandle<R>, pool: &Pool<Sqlite>) -> Result<()> {
+    let quota = db::get_meta(pool, QUOTA_KEY)
+        .await?
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|q| *q > 0)
+        .unwrap_or(DEFAULT_QUOTA);
+    let done: Vec<DownloadRow> = db::list_downloads(pool)
+        .await?
+        .into_iter()
+        .filter(|row| row.status == DownloadStatus::Done.as_str())
+        .collect();
+    let mut used: i64 = done.iter().map(|row| row.bytes_downloaded).sum();
+    let mut candidates: Vec<&DownloadRow> = done
+        .iter()
+        .filter(|row| row.played_at.is_some() || row.auto)
+        .collect();
+    candidates.sort_by_key(|row| {
+        (
+            row.played_at.is_none(),
+            row.played_at.unwrap_or(0),
+            row.completed_at.unwrap_or(0),
+        )
+    });
+    let manager = app.state::<DownloadManager>();
+    for row in candidates {
+        if used <= quota {
+            break;
+        }
+        manager.delete(app, row.enclosure_id).await?;
+        used -= row.bytes_downloaded;
+    }
+    Ok(())
+}
+
+/// Files whose enclosure went away with a pruned article.
+async fn remove_orphans<R: Runtime>(app: &AppHandle<R>, pool: &Pool<Sqlite>) -> Result<()> {
+    let known: Vec<i64> = db::list_downloads(pool)
+        .await?
+        .iter()
+        .map(|row| row.enclosure_id)
+        .collect();
+    let Ok(mut entries) = tokio::fs::read_dir(downloads_dir(app)?).await else {
+        return Ok(());
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        let id = entry
+            .path()
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse::<i64>().ok());
+        if id.is_some_and(|id| !known.contains(&id)) {
+            let _ = tokio::fs::remove_file(entry.path()).await;
+        }
+    }
+    Ok(())
+}
+
+/// Removes an enclosure's episode and partial file. They are found by name
+/// in the downloads directory rather than through the stored path, which
+/// the webview can write.
+async fn remove_files<R: Runtime>(app: &AppHandle<R>, enclosure_id: i64) {
+    let Ok(dir) = downloads_dir(app) else {
+        return;
+    };
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let id = entry
+            .path()
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse::<i64>().ok());
+        if id == Some(enclosure_id) {
+            let _ = tokio::fs::remove_file(entry.path()).await;
+        }
+    }
+}
+
+fn downloads_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf> {
+    Ok(app.path().app_data_dir()?.join(DIR))
+}
+
+fn part_path(dir: &Path, enclosure_id: i64) -> PathBuf {
+    dir.join(format!("{enclosure_id}.part"))
+}
+
+/// Keeps the extension from the URL so the webview can guess the media
+/// type, falling back to the declared MIME type.
+fn extension(url: &str, mime_type: Option<&str>) -> String {
+    let from_url = url::Url::parse(url).ok().and_then(|u| {
+        Path::new(u.path())
+            .extension()
+            .and_then(|e| e.to_str())
+            .filter(|e| e.len() <= 5 && e.chars().all(|c| c.is_ascii_alphanumeric()))
+            .map(str::to_ascii_lowercase)
+    });
+    from_url.unwrap_or_else(|| {
+        match mime_type.unwrap_or_default() {
+            "audio/mpeg" => "mp3",
+            "audio/mp4" | "audio/x-m4a" => "m4a",
+            "audio/ogg" => "ogg",
+            "video/mp4" => "mp4",
+            _ => "bin",
+        }
+        .to_string()
+    })
+}
@@ -0,0 +1,186 @@
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Serialize, Serializer};
+use tauri_plugin_http::reqwest;
+
+#[derive(Debug)]
+pub enum Error {
+    Xml(roxmltree::Error),
+    Json(serde_json::Error),
+    UnsupportedFormat(String),
+    Http(reqwest::Error),
+    Status(u16),
+    Gone,
+    TooManyRedirects(String),
+    Throttled {
+        status: u16,
+        retry_after: Option<Duration>,
+    },
+    HostPaused(Duration),
+    Database(sqlx::Error),
+    Io(std::io::Error),
+    DatabaseNotLoaded,
+    FeedNotFound(String),
+    NotFetched(String),
+    ArticleNotFound(String),
+    RevisionNotFound(i64),
+    EnclosureNotFound(i64),
+    NotAnImage(String),
+    TooLarge(String),
+    NoArticleContent(String),
+    InvalidSelector(String),
+    NoItemsMatched(String),
+    InvalidUrl(String),
+    FeedFile(String, std::io::Error),
+    CommandSourcesDisabled,
+    CommandFailed {
+        status: Option<i32>,
+        stderr: String,
+    },
+    CommandTimedOut(Duration),
+    Task(tokio::task::JoinError),
+    Tauri(tauri::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Xml(e) => write!(f, "invalid XML: {e}"),
+            Error::Json(e) => write!(f, "invalid JSON: {e}"),
+            Error::UnsupportedFormat(root) => write!(f, "unsupported feed format: <{root}>"),
+            Error::Http(e) => write!(f, "request failed: {e}"),
+            Error::Status(code) => write!(f, "HTTP {code}"),
+            Error::Gone => write!(f, "feed is gone (HTTP 410)"),
+            Error::TooManyRedirects(url) => write!(f, "too many redirects from {url}"),
+            Error::Throttled {
+                status,
+                retry_after: Some(after),
+            } => write!(f, "HTTP {status} (retry after {}s)", after.as_secs()),
+            Error::Throttled { status, .. } => write!(f, "HTTP {status}"),
+            Error::HostPaused(wait) => {
+                write!(f, "host asked to wait another {}s", wait.as_secs())
+            }
+            Error::Database(e) => write!(f, "database error: {e}"),
+            Error::Io(e) => write!(f, "file error: {e}"),
+            Error::DatabaseNotLoaded => write!(f, "database is not loaded"),
+            Error::FeedNotFound(url) => write!(f, "not subscribed to {url}"),
+            Error::NotFetched(url) => write!(f, "{url} is filled from a mailbox, not fetched"),
+            Error::ArticleNotFound(id) => write!(f, "no article {id}"),
+            Error::RevisionNotFound(id) => write!(f, "no revision {id}"),
+            Error::EnclosureNotFound(id) => write!(f, "no enclosure {id}"),
+            Error::NotAnImage(url) => write!(f, "not an image: {url}"),
+            Error::TooLarge(url) => write!(f, "too large: {url}"),
+            Error::NoArticleContent(url) => write!(f, "no article text found at {url}"),
+            Error::InvalidSelector(selector) => write!(f, "invalid CSS selector: {selector}"),
+            Error::NoItemsMatched(selector) => write!(f, "nothing on the page matches {selector}"),
+            Error::InvalidUrl(url) => write!(f, "invalid URL: {url}"),
+            Error::FeedFile(path, e) => write!(f, "could not read {path}: {e}"),
+            Error::CommandSourcesDisabled => {
+                write!(
+                    f,
+                    "exec: and filter: sources are off; set \"commandSources\": true in {} in the app config directory",
+                    crate::exec::CONFIG_FILE
+                )
+            }
+            Error::CommandFailed {
+                status: Some(code),
+                stderr,
+            } => write!(f, "command exited with status {code}: {stderr}"),
+            Error::CommandFailed { stderr, .. } => write!(f, "command failed: {stderr}"),
+            Error::CommandTimedOut(after) => {
+                write!(f, "command timed out after {}s", after.as_secs())
+            }
+            Error::Task(e) => write!(f, "background task failed: {e}"),
+            Error::Tauri(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Error {
+    /// The HTTP status behind a failed fetch, if the server answered at all.
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            Error::Status(status) | Error::Throttled { status, .. } => Some(*status),
+            Error::Gone => Some(410),
+            Error::Http(e) => e.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+
+    /// Whether the error says something about the feed itself, as opposed
+    /// to the app's own state.
+    pub fn is_feed_failure(&self) -> bool {
+        matches!(
+            self,
+            Error::Xml(_)
+                | Error::Json(_)
+                | Error::UnsupportedFormat(_)
+                | Error::Http(_)
+                | Error::Status(_)
+                | Error::Gone
+                | Error::TooManyRedirects(_)
+                | Error::Throttled { .. }
+                | Error::InvalidUrl(_)
+                | Error::InvalidSelector(_)
+                | Error::NoItemsMatched(_)
+                | Error::FeedFile(..)
+                | Error::CommandSourcesDisabled
+                | Error::CommandFailed { .. }
+                | Error::CommandTimedOut(_)
+        )
+    }
+}
+
+impl From<roxmltree::Error> for Error {
+    fn from(e: roxmltree::Error) -> Self {
+        Error::Xml(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Http(e)
+    }
+}
+
+impl From<sqlx::Error> for Error {
+    fn from(e: sqlx::Error) -> Self {
+        Error::Database(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<tokio::task::JoinError> for Error {
+    fn from(e: tokio::task::JoinError) -> Self {
+        Error::Task(e)
+    }
+}
+
+impl From<tauri::Error> for Error {
+    fn from(e: tauri::Error) -> Self {
+        Error::Tauri(e)
+    }
+}
+
+// Commands hand errors to the webview, which only needs the message.
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,181 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::error::{Error, Result};
+use crate::mail;
+
+/// Command sources run arbitrary programs, so they stay off until the user
+/// sets `"commandSources": true` in this file in the app config directory.
+/// The webview can write the database, so the switch can't live there.
+pub const CONFIG_FILE: &str = "config.json";
+
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Config {
+    #[serde(default)]
+    command_sources: bool,
+}
+const TIMEOUT: Duration = Duration::from_secs(60);
+/// How much of a failing command's stderr ends up in the feed's error.
+const MAX_STDERR_CHARS: usize = 2000;
+
+/// Where a subscription's feed comes from, going by newsboat's URL syntax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source<'a> {
+    Http(&'a str),
+    /// A `file://` URL, read straight from disk.
+    File(&'a str),
+    /// A newsletter sender, filled by the mailbox importer rather than
+    /// fetched.
+    Mail(&'a str),
+    /// `exec:<command>`: the command's output is the feed.
+    Exec(&'a str),
+    /// `filter:<command>:<url>`: the feed at `url`, piped through the
+    /// command.
+    Filter {
+        command: &'a str,
+        url: &'a str,
+    },
+}
+
+pub fn source(url: &str) -> Source<'_> {
+    if let Some(command) = url.strip_prefix("exec:") {
+        return Source::Exec(command.trim());
+    }
+    if let Some(rest) = url.strip_prefix("filter:") {
+        // The command may contain colons itself, so split before the URL.
+        let split = ["http://", "https://"]
+            .iter()
+            .filter_map(|scheme| rest.find(&format!(":{scheme}")))
+            .min();
+        if let Some(at) = split {
+            return Source::Filter {
+                command: rest[..at].trim(),
+                url: &rest[at + 1..],
+            };
+        }
+    }
+    if url
+        .get(..5)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("file:"))
+    {
+        return Source::File(url);
+    }
+    if url.starts_with(mail::SCHEME) {
+        return Source::Mail(url);
+    }
+    Source::Http(url)
+}
+
+/// The address relative links in the feed resolve against.
+pub fn document_url(url: &str) -> &str {
+    match source(url) {
+        Source::Filter { url, .. } => url,
+        _ => url,
+    }
+}
+
+/// Reads the command source switch from `CONFIG_FILE` in `config_dir`
+/// from now on.
+pub fn init(config_dir: &Path) {
+    let _ = CONFIG_PATH.set(config_dir.join(CONFIG_FILE));
+}
+
+/// Whether the user allowed command sources. The file is read on every
+/// call, so editing it takes effect on the next refresh.
+pub async fn enabled() -> bool {
+    let Some(path) = CONFIG_PATH.get() else {
+        return false;
+    };
+    let Ok(bytes) = tokio::fs::read(path).await else {
+        return false;
+    };
+    match serde_json::from_slice::<Config>(&bytes) {
+        Ok(config) => config.command_sources,
+        Err(e) => {
+            log::warn!("could not read {}: {e}", path.display());
+            false
+        }
+    }
+}
+
+/// Runs `command` through the shell, feeding it `input`, and returns what
+/// it writes to stdout. A nonzero exit fails with the command's stderr.
+pub async fn run(command: &str, input: Option<&[u8]>) -> Result<Vec<u8>> {
+    let mut child = shell(command)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| Error::CommandFailed {
+            status: None,
+            stderr: e.to_string(),
+        })?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        let input = input.to_vec();
+        // Written alongside reading stdout, so a filter that streams its
+        // output can't fill the pipe and stall both sides.
+        tauri::async_runtime::spawn(async move {
+            let _ = stdin.write_all(&input).await;
+        });
+    }
+    let pid = child.id();
+    let output = match tokio::time::timeout(TIMEOUT, child.wait_with_output()).await {
+        Ok(output) => output?,
+        Err(_) => {
+            kill_group(pid);
+            return Err(Error::CommandTimedOut(TIMEOUT));
+        }
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::CommandFailed {
+            status: output.status.code(),
+            stderr: stderr.trim().chars().take(MAX_STDERR_CHARS).collect(),
+        });
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+/// Runs in a process group of its own, so a timeout can take down the
+/// whole pipeline and not just the shell.
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command).process_group(0);
+    cmd
+}
+
+#[cfg(windows)]
+fn kill_group(_pid: Option<u32>) {}
+
+#[cfg(not(windows))]
+fn kill_group(pid: Option<u32>) {
+    if let Some(pid) = pid.and_then(|pid| i32::try_from(pid).ok()) {
+        // SAFETY: killpg has no memory effects; the group is the one the
+        // shell leads, since it was spawned with `process_group(0)`.
+        unsafe {
+            libc::killpg(pid, libc::SIGKILL);
+        }
+    }
+}
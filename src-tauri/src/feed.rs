@@ -0,0 +1,681 @@
+use roxmltree::{Document, Node, ParsingOptions};
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::charset;
+use crate::date;
+use crate::error::{Error, Result};
+use crate::json_feed;
+
+const ATOM_NS: &str = "http://www.w3.org/2005/Atom";
+const RSS1_NS: &str = "http://purl.org/rss/1.0/";
+const RDF_NS: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const CONTENT_NS: &str = "http://purl.org/rss/1.0/modules/content/";
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+const SY_NS: &str = "http://purl.org/rss/1.0/modules/syndication/";
+const ITUNES_NS: &str = "http://www.itunes.com/dtds/podcast-1.0.dtd";
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+// Publishers use the Media RSS namespace with and without the trailing slash.
+const MEDIA_NS: [&str; 2] = [
+    "http://search.yahoo.com/mrss/",
+    "http://search.yahoo.com/mrss",
+];
+
+pub(crate) const DAY_NAMES: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedFormat {
+    Rss2,
+    Rss09,
+    Rss1,
+    Atom,
+    JsonFeed,
+    /// Items scraped from an HTML page with CSS selectors.
+    Html,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Feed {
+    pub format: FeedFormat,
+    pub title: String,
+    pub link: Option<String>,
+    pub description: Option<String>,
+    /// The feed's own icon: RSS `<image>`, Atom `<icon>` or `<logo>`, JSON
+    /// Feed `favicon` or `icon`, or the podcast's artwork.
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub schedule: Schedule,
+    pub items: Vec<Item>,
+    /// The `xml:base` on the feed's root or channel, if any.
+    #[serde(skip)]
+    pub base: Option<String>,
+}
+
+/// How often the feed says it should be polled.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    /// From RSS `<ttl>`, or `sy:updatePeriod` divided by `sy:updateFrequency`.
+    pub interval_minutes: Option<u32>,
+    /// GMT hours (0-23) from `<skipHours>`.
+    pub skip_hours: Vec<u8>,
+    /// English day names from `<skipDays>`, capitalised as in the spec.
+    pub skip_days: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Item {
+    /// Stable identity within the feed: the item's guid, or a hash of its
+    /// link, title and date when it has none.
+    pub id: String,
+    /// RSS `<guid>`, RSS 1.0 `rdf:about`, Atom `<id>` or JSON Feed `id`.
+    pub guid: Option<String>,
+    pub title: String,
+    pub link: Option<String>,
+    /// The link as the feed wrote it, before `Feed::resolve_urls` made it
+    /// absolute.
+    #[serde(skip)]
+    pub original_link: Option<String>,
+    pub published: Option<String>,
+    /// `published` normalized to milliseconds since the epoch.
+    pub published_ts: Option<i64>,
+    pub content: Option<String>,
+    pub author: Option<String>,
+    pub enclosures: Vec<Enclosure>,
+    pub episode: Episode,
+    /// Lead image for list thumbnails: `media:thumbnail`, an image
+    /// `media:content`, JSON Feed `image`, or else the first `<img>` in the
+    /// content.
+    pub image: Option<String>,
+    /// What relative URLs in `content` are relative to: the `xml:base` in
+    /// scope, completed by `Feed::resolve_urls` from the item link or feed.
+    #[serde(skip)]
+    pub base: Option<String>,
+}
+
+/// An attached media file: RSS `<enclosure>`, Atom `link rel="enclosure"`
+/// or a JSON Feed attachment.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Enclosure {
+    pub url: String,
+    pub mime_type: Option<String>,
+    /// Size in bytes.
+    pub length: Option<i64>,
+    /// Running time in seconds.
+    pub duration: Option<i64>,
+}
+
+/// Podcast metadata from the `itunes:` namespace.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Episode {
+    pub number: Option<i64>,
+    pub season: Option<i64>,
+    /// The episode's artwork, falling back to the show's.
+    pub image: Option<String>,
+    pub explicit: Option<bool>,
+}
+
+impl Feed {
+    /// Makes the feed's links absolute. Item links resolve against
+    /// `xml:base`, then the channel link, then `feed_url`; item media and
+    /// `base` (for content, resolved at storage) additionally prefer the
+    /// item's own link.
+    pub fn resolve_urls(&mut self, feed_url: &str) {
+        let Ok(feed_url) = Url::parse(feed_url) else {
+            return;
+        };
+        let document = join(&feed_url, self.base.as_deref()).unwrap_or_else(|| feed_url.clone());
+        let site = join(&document, self.link.as_deref());
+        self.link = site.as_ref().map(Url::to_string).or(self.link.take());
+        let site = site.unwrap_or(document);
+        self.icon = resolve(&site, self.icon.take());
+
+        for item in &mut self.items {
+            let xml_base = join(&feed_url, item.base.as_deref());
+            let link = join(xml_base.as_ref().unwrap_or(&site), item.link.as_deref());
+            let base = xml_base
+                .or_else(|| link.clone())
+                .unwrap_or_else(|| site.clone());
+            item.original_link = item.link.clone();
+            item.link = link.map(String::from).or(item.link.take());
+            item.image = resolve(&base, item.image.take());
+            item.episode.image = resolve(&base, item.episode.image.take());
+            for enclosure in &mut item.enclosures {
+                if let Some(url) = join(&base, Some(&enclosure.url)) {
+                    enclosure.url = url.into();
+                }
+            }
+            item.base = Some(base.into());
+        }
+    }
+}
+
+fn join(base: &Url, url: Option<&str>) -> Option<Url> {
+    base.join(url?.trim()).ok()
+}
+
+fn resolve(base: &Url, url: Option<String>) -> Option<String> {
+    let resolved = join(base, url.as_deref())?;
+    Some(resolved.into())
+}
+
+pub fn parse(text: &str) -> Result<Feed> {
+    parse_with_content_type(text, None)
+}
+
+/// Parses a raw response body, transcoding it to UTF-8 first.
+pub fn parse_bytes(bytes: &[u8], content_type: Option<&str>) -> Result<Feed> {
+    parse_with_content_type(&charset::decode(bytes, content_type), content_type)
+}
+
+/// Picks JSON Feed or XML by sniffing the body, falling back to the
+/// Content-Type header when the body doesn't start with `{` or `<`.
+pub fn parse_with_content_type(text: &str, content_type: Option<&str>) -> Result<Feed> {
+    let body = text.trim_start_matches('\u{feff}');
+    let is_json = match body.trim_start().chars().next() {
+        Some('{') => true,
+        Some('<') => false,
+        _ => content_type.is_some_and(|ct| ct.to_ascii_lowercase().contains("json")),
+    };
+    let mut feed = if is_json {
+        json_feed::parse(body)?
+    } else {
+        parse_xml(body)?
+    };
+    fill_in_items(&mut feed.items);
+    Ok(feed)
+}
+
+/// Derives what every format computes the same way: timestamps, ids and
+/// lead images taken from the content.
+pub(crate) fn fill_in_items(items: &mut [Item]) {
+    for item in items {
+        item.published_ts = item.published.as_deref().and_then(date::timestamp_ms);
+        item.id = item_id(item);
+        if item.image.is_none() {
+            item.image = item.content.as_deref().and_then(first_image);
+        }
+    }
+}
+
+fn parse_xml(text: &str) -> Result<Feed> {
+    // RSS 0.91 feeds commonly still carry the Netscape DOCTYPE.
+    let opts = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let doc = Document::parse_with_options(text, opts)?;
+    let root = doc.root_element();
+
+    match (root.tag_name().namespace(), root.tag_name().name()) {
+        (Some(ATOM_NS), "feed") => Ok(parse_atom(root)),
+        (Some(RDF_NS), "RDF") => Ok(parse_rdf(root)),
+        (None, "rss") => {
+            let format = match root.attribute("version") {
+                Some(v) if v.starts_with("0.9") => FeedFormat::Rss09,
+                _ => FeedFormat::Rss2,
+            };
+            let channel = child(root, None, "channel")
+                .ok_or_else(|| Error::UnsupportedFormat("rss without <channel>".into()))?;
+            Ok(parse_rss_channel(format, channel, channel))
+        }
+        (_, name) => Err(Error::UnsupportedFormat(name.to_string())),
+    }
+}
+
+fn parse_rss_channel(format: FeedFormat, channel: Node, item_parent: Node) -> Feed {
+    let ns = channel.tag_name().namespace();
+    let items = item_parent
+        .children()
+        .filter(|n| is(*n, ns, "item"))
+        .map(|item| Item {
+            guid: child_text(item, ns, "guid")
+                .or_else(|| item.attribute((RDF_NS, "about")).map(str::to_string)),
+            title: child_text(item, ns, "title").unwrap_or_else(untitled),
+            link: child_text(item, ns, "link"),
+            published: child_text(item, ns, "pubDate")
+                .or_else(|| child_text(item, Some(DC_NS), "date")),
+            content: child_text(item, Some(CONTENT_NS), "encoded")
+                .or_else(|| child_text(item, ns, "description")),
+            author: child_text(item, Some(DC_NS), "creator")
+                .or_else(|| child_text(item, ns, "author")),
+            enclosures: with_itunes_duration(
+                item.children()
+                    .filter(|n| is(*n, ns, "enclosure"))
+                    .filter_map(|n| {
+                        enclosure(
+                            n.attribute("url")?,
+                            n.attribute("type"),
+                            n.attribute("length"),
+                        )
+                    })
+                    .collect(),
+                item,
+            ),
+            episode: episode(item, channel),
+            image: media_image(item),
+            base: xml_base(item),
+            ..Item::default()
+        })
+        .collect();
+
+    Feed {
+        format,
+        title: child_text(channel, ns, "title").unwrap_or_else(untitled),
+        link: child_text(channel, ns, "link"),
+        description: child_text(channel, ns, "description"),
+        icon: child(channel, ns, "image")
+            .and_then(|image| {
+                child_text(image, ns, "url")
+                    .or_else(|| image.attribute((RDF_NS, "resource")).map(str::to_string))
+            })
+            .or_else(|| {
+                child(channel, Some(ITUNES_NS), "image")
+                    .and_then(|i| i.attribute("href"))
+                    .map(|href| href.trim().to_string())
+                    .filter(|href| !href.is_empty())
+            }),
+        schedule: Schedule {
+            interval_minutes: child_text(channel, ns, "ttl")
+                .and_then(|ttl| ttl.parse().ok())
+                .filter(|m| *m > 0)
+                .or_else(|| sy_interval(channel)),
+            skip_hours: child(channel, ns, "skipHours")
+                .map(|skip| {
+                    skip.children()
+                        .filter(|n| is(*n, ns, "hour"))
+                        .filter_map(|n| text(n).trim().parse::<u8>().ok())
+                        // Some feeds count 1-24 instead of 0-23.
+                        .map(|h| h % 24)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            skip_days: child(channel, ns, "skipDays")
+                .map(|skip| {
+                    skip.children()
+                        .filter(|n| is(*n, ns, "day"))
+                        .filter_map(|n| day_name(text(n).trim()))
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        },
+        items,
+        base: xml_base(channel),
+    }
+}
+
+// RSS 1.0 puts <item> elements next to <channel> rather than inside it.
+fn parse_rdf(root: Node) -> Feed {
+    match child(root, Some(RSS1_NS), "channel") {
+        Some(channel) => parse_rss_channel(FeedFormat::Rss1, channel, root),
+        None => Feed {
+            format: FeedFormat::Rss1,
+            title: untitled(),
+            link: None,
+            description: None,
+            icon: None,
+            schedule: Schedule::default(),
+            items: Vec::new(),
+            base: None,
+        },
+    }
+}
+
+fn parse_atom(root: Node) -> Feed {
+    let ns = Some(ATOM_NS);
+    let items = root
+        .children()
+        .filter(|n| is(*n, ns, "entry"))
+        .map(|entry| Item {
+            guid: child_text(entry, ns, "id"),
+            title: child(entry, ns, "title")
+                .and_then(atom_text)
+                .unwrap_or_else(untitled),
+            link: atom_link(entry),
+            published: child_text(entry, ns, "published")
+                .or_else(|| child_text(entry, ns, "updated")),
+            content: child(entry, ns, "content")
+                .and_then(atom_text)
+                .or_else(|| child(entry, ns, "summary").and_then(atom_text)),
+            author: child(entry, ns, "author")
+                .or_else(|| child(root, ns, "author"))
+                .and_then(|a| child_text(a, ns, "name")),
+            enclosures: with_itunes_duration(
+                entry
+                    .children()
+                    .filter(|n| is(*n, ns, "link") && n.attribute("rel") == Some("enclosure"))
+                    .filter_map(|n| {
+                        enclosure(
+                            n.attribute("href")?,
+                            n.attribute("type"),
+                            n.attribute("length"),
+                        )
+                    })
+                    .collect(),
+                entry,
+            ),
+            episode: episode(entry, root),
+            image: media_image(entry),
+            base: xml_base(entry),
+            ..Item::default()
+        })
+        .collect();
+
+    Feed {
+        format: FeedFormat::Atom,
+        title: child(root, ns, "title")
+            .and_then(atom_text)
+            .unwrap_or_else(untitled),
+        link: atom_link(root),
+        description: child(root, ns, "subtitle").and_then(atom_text),
+        icon: child_text(root, ns, "icon").or_else(|| child_text(root, ns, "logo")),
+        schedule: Schedule {
+            interval_minutes: sy_interval(root),
+            ..Schedule::default()
+        },
+        items,
+        base: xml_base(root),
+    }
+}
+
+fn item_id(item: &Item) -> String {
+    match &item.guid {
+        Some(guid) => guid.clone(),
+        None => hashed_id(item.link.as_deref(), &item.title, item.published.as_deref()),
+    }
+}
+
+/// The id of an item without a guid.
+pub(crate) fn hashed_id(link: Option<&str>, title: &str, published: Option<&str>) -> String {
+    let link = link.map(strip_tracking).unwrap_or_default();
+    let published = published.unwrap_or_default();
+    let key = format!("{link}\n{title}\n{published}");
+    format!("hash:{:016x}", fnv1a(key.as_bytes()))
+}
+
+/// Drops `utm_*` query parameters, which some feeds rotate on every fetch.
+fn strip_tracking(link: &str) -> String {
+    let Ok(mut url) = Url::parse(link) else {
+        return link.to_string();
+    };
+    let kept: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| !name.starts_with("utm_"))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    if kept.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(kept);
+    }
+    url.to_string()
+}
+
+/// 64-bit FNV-1a. Ids are stored, so the hash must never change between
+/// builds the way std's `DefaultHasher` may.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Reads the syndication module's `updatePeriod` and `updateFrequency`, where
+/// a frequency of 2 with a daily period means twice a day.
+fn sy_interval(node: Node) -> Option<u32> {
+    let period = match child_text(node, Some(SY_NS), "updatePeriod")?.as_str() {
+        "hourly" => 60,
+        "daily" => 60 * 24,
+        "weekly" => 60 * 24 * 7,
+        "monthly" => 60 * 24 * 30,
+        "yearly" => 60 * 24 * 365,
+        _ => return None,
+    };
+    let frequency = child_text(node, Some(SY_NS), "updateFrequency")
+        .and_then(|f| f.parse::<u32>().ok())
+        .filter(|f| *f > 0)
+        .unwrap_or(1);
+    Some((period / frequency).max(1))
+}
+
+fn day_name(day: &str) -> Option<&'static str> {
+    DAY_NAMES
+        .into_iter()
+        .find(|name| name.eq_ignore_ascii_case(day))
+}
+
+fn enclosure(url: &str, mime_type: Option<&str>, length: Option<&str>) -> Option<Enclosure> {
+    let url = url.trim();
+    if url.is_empty() {
+        return None;
+    }
+    Some(Enclosure {
+        url: url.to_string(),
+        mime_type: mime_type
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string),
+        // Publishers often send 0 or junk when they don't know the size.
+        length: length
+            .and_then(|l| l.trim().parse().ok())
+            .filter(|l| *l > 0),
+        duration: None,
+    })
+}
+
+/// `itunes:duration` describes the episode's audio, which is its first
+/// enclosure.
+fn with_itunes_duration(mut enclosures: Vec<Enclosure>, item: Node) -> Vec<Enclosure> {
+    if let Some(first) = enclosures.first_mut() {
+        first.duration =
+            child_text(item, Some(ITUNES_NS), "duration").and_then(|d| parse_duration(&d));
+    }
+    enclosures
+}
+
+/// Accepts plain seconds, `MM:SS` and `HH:MM:SS`.
+fn parse_duration(value: &str) -> Option<i64> {
+    let mut seconds: i64 = 0;
+    for part in value.trim().split(':') {
+        let part: f64 = part.trim().parse().ok()?;
+        // `as` saturates, so reject anything it can't represent rather than
+        // let the arithmetic below overflow on a hostile feed.
+        if !(0.0..i64::MAX as f64).contains(&part) {
+            return None;
+        }
+        seconds = seconds.checked_mul(60)?.checked_add(part as i64)?;
+    }
+    Some(seconds).filter(|s| *s > 0)
+}
+
+fn episode(item: Node, channel: Node) -> Episode {
+    let number = |name| {
+        child_text(item, Some(ITUNES_NS), name)
+            .and_then(|n| n.parse().ok())
+            .filter(|n: &i64| *n > 0)
+    };
+    let image = |node| {
+        child(node, Some(ITUNES_NS), "image")
+            .and_then(|i| i.attribute("href"))
+            .map(str::trim)
+            .filter(|href| !href.is_empty())
+            .map(str::to_string)
+    };
+    Episode {
+        number: number("episode"),
+        season: number("season"),
+        image: image(item).or_else(|| image(channel)),
+        explicit: child_text(item, Some(ITUNES_NS), "explicit")
+            .or_else(|| child_text(channel, Some(ITUNES_NS), "explicit"))
+            .and_then(|e| match e.to_ascii_lowercase().as_str() {
+                "yes" | "true" | "explicit" => Some(true),
+                "no" | "false" | "clean" => Some(false),
+                _ => None,
+            }),
+    }
+}
+
+/// Combines the `xml:base` attributes on a node and its ancestors. The
+/// result may still be relative to the document's own URL.
+fn xml_base(node: Node) -> Option<String> {
+    let mut bases: Vec<&str> = node
+        .ancestors()
+        .filter_map(|n| n.attribute((XML_NS, "base")))
+        .map(str::trim)
+        .collect();
+    bases.reverse();
+    let mut combined: Option<String> = None;
+    for base in bases {
+        combined = Some(match combined.as_deref().map(Url::parse) {
+            Some(Ok(outer)) => outer
+                .join(base)
+                .map_or_else(|_| base.to_string(), String::from),
+            _ => base.to_string(),
+        });
+    }
+    combined
+}
+
+/// The item's `media:thumbnail`, or failing that a `media:content` that is
+/// an image, whether directly on the item or inside a `media:group`.
+fn media_image(item: Node) -> Option<String> {
+    let media = |name| {
+        item.descendants()
+            .filter(move |n| MEDIA_NS.iter().any(|ns| is(*n, Some(ns), name)))
+    };
+    media("thumbnail")
+        .chain(media("content").filter(|n| is_image(*n)))
+        .filter_map(|n| n.attribute("url"))
+        .map(str::trim)
+        .find(|url| !url.is_empty())
+        .map(str::to_string)
+}
+
+fn is_image(content: Node) -> bool {
+    match (content.attribute("medium"), content.attribute("type")) {
+        (Some(medium), _) => medium.trim().eq_ignore_ascii_case("image"),
+        (None, Some(ty)) => ty.trim().to_ascii_lowercase().starts_with("image/"),
+        (None, None) => content.attribute("url").is_some_and(|url| {
+            let path = url.split(['?', '#']).next().unwrap_or_default();
+            let path = path.to_ascii_lowercase();
+            [".jpg", ".jpeg", ".png", ".gif", ".webp", ".avif"]
+                .iter()
+                .any(|ext| path.ends_with(ext))
+        }),
+    }
+}
+
+/// The first `<img>` in an item's HTML, skipping 1x1 tracking pixels and
+/// inline data.
+fn first_image(html: &str) -> Option<String> {
+    let images = Selector::parse("img[src]").ok()?;
+    Html::parse_fragment(html)
+        .select(&images)
+        .filter(|img| {
+            !["width", "height"]
+                .iter()
+                .any(|dim| img.value().attr(dim).is_some_and(|v| v.trim() == "1"))
+        })
+        .filter_map(|img| img.value().attr("src"))
+        .map(str::trim)
+        .find(|src| !src.is_empty() && !src.starts_with("data:"))
+        .map(str::to_string)
+}
+
+/// Prefers `rel="alternate"` (the default when `rel` is absent) over any
+/// other link except enclosures.
+fn atom_link(node: Node) -> Option<String> {
+    let links: Vec<Node> = node
+        .children()
+        .filter(|n| is(*n, Some(ATOM_NS), "link"))
+        .collect();
+    links
+        .iter()
+        .find(|l| matches!(l.attribute("rel"), None | Some("alternate")))
+        .or_else(|| {
+            links
+                .iter()
+                .find(|l| l.attribute("rel") != Some("enclosure"))
+        })
+        .and_then(|l| l.attribute("href"))
+        .map(|href| href.trim().to_string())
+        .filter(|href| !href.is_empty())
+}
+
+/// Atom text constructs may be plain text, escaped HTML or inline XHTML.
+fn atom_text(node: Node) -> Option<String> {
+    let value = if node.attribute("type") == Some("xhtml") {
+        inner_xml(node)
+    } else {
+        text(node)
+    };
+    non_empty(value)
+}
+
+/// Returns the raw markup between a node's start and end tags.
+fn inner_xml(node: Node) -> String {
+    let mut children = node.children();
+    // Inline XHTML is wrapped in a single <div> that isn't part of the content.
+    let wrapper = node
+        .children()
+        .find(|n| n.is_element() && n.tag_name().name() == "div");
+    if let Some(div) = wrapper {
+        children = div.children();
+    }
+    let nodes: Vec<Node> = children.collect();
+    match (nodes.first(), nodes.last()) {
+        (Some(first), Some(last)) => {
+            let input = node.document().input_text();
+            input[first.range().start..last.range().end].to_string()
+        }
+        _ => String::new(),
+    }
+}
+
+/// Concatenates text and CDATA children, which the parser keeps as separate nodes.
+fn text(node: Node) -> String {
+    node.children()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect()
+}
+
+fn is(node: Node, ns: Option<&str>, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name && node.tag_name().namespace() == ns
+}
+
+fn child<'a, 'i>(node: Node<'a, 'i>, ns: Option<&str>, name: &str) -> Option<Node<'a, 'i>> {
+    node.children().find(|n| is(*n, ns, name))
+}
+
+fn child_text(node: Node, ns: Option<&str>, name: &str) -> Option<String> {
+    child(node, ns, name).and_then(|n| non_empty(text(n)))
+}
+
+fn non_empty(s: String) -> Option<String> {
+    let trimmed = s.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+fn untitled() -> String {
+    "Untitled".to_string()
+}
@@ -0,0 +1,312 @@
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use tauri_plugin_http::reqwest::{
+    Client, Response, StatusCode,
+    header::{
+        ACCEPT, CONTENT_TYPE, ETAG, HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+        LOCATION, RANGE, RETRY_AFTER,
+    },
+    redirect,
+};
+use url::Url;
+
+use crate::charset;
+use crate::error::{Error, Result};
+use crate::exec::{self, Source};
+
+const ACCEPT_FEEDS: &str = "application/rss+xml, application/atom+xml, application/feed+json, application/xml, text/xml, */*";
+
+const MAX_REDIRECTS: usize = 10;
+
+/// A server that accepts the connection and then stops sending would
+/// otherwise hold its refresh or download slot forever.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// Feeds are small, so a fetch still running after this is stuck.
+const FEED_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Also carries episode downloads, so it only gives up on stalled reads
+/// rather than capping the whole transfer.
+static CLIENT: LazyLock<Client> =
+    LazyLock::new(|| client(redirect::Policy::limited(MAX_REDIRECTS), None));
+
+/// Feed fetches follow redirects by hand so they can tell whether the feed
+/// moved for good.
+static FEED_CLIENT: LazyLock<Client> =
+    LazyLock::new(|| client(redirect::Policy::none(), Some(FEED_TIMEOUT)));
+
+fn client(policy: redirect::Policy, timeout: Option<Duration>) -> Client {
+    let mut builder = Client::builder()
+        .user_agent("Lector/1.0")
+        .connect_timeout(Duration::from_secs(12))
+        .read_timeout(READ_TIMEOUT)
+        .redirect(policy);
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    builder.build().expect("failed to build HTTP client")
+}
+
+/// Cache validators from a previous response, replayed as a conditional GET.
+#[derive(Debug, Clone, Default)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+pub enum Fetched {
+    NotModified,
+    Body {
+        /// Raw bytes, left for the parser to decode once it has sniffed the
+        /// encoding.
+        body: Vec<u8>,
+        content_type: Option<String>,
+        validators: Validators,
+    },
+}
+
+pub struct FeedResponse {
+    /// Where the feed now lives, when every redirect on the way was a
+    /// 301 or 308.
+    pub moved_to: Option<String>,
+    pub fetched: Fetched,
+}
+
+/// Fetches a feed over HTTP, or for `exec:` and `filter:` sources runs the
+/// command, which the caller must have allowed with `allow_commands`.
+pub async fn fetch(
+    url: &str,
+    validators: &Validators,
+    allow_commands: bool,
+) -> Result<FeedResponse> {
+    let source = exec::source(url);
+    if !allow_commands && matches!(source, Source::Exec(_) | Source::Filter { .. }) {
+        return Err(Error::CommandSourcesDisabled);
+    }
+    match source {
+        Source::Http(url) => fetch_http(url, validators).await,
+        Source::File(url) => fetch_file(url, validators).await,
+        Source::Mail(url) => Err(Error::NotFetched(url.to_string())),
+        Source::Exec(command) => Ok(FeedResponse {
+            moved_to: None,
+            fetched: Fetched::Body {
+                body: exec::run(command, None).await?,
+                content_type: None,
+                validators: Validators::default(),
+            },
+        }),
+        Source::Filter { command, url } => {
+            let response = fetch_http(url, validators).await?;
+            let fetched = match response.fetched {
+                Fetched::NotModified => Fetched::NotModified,
+                Fetched::Body {
+                    body,
+                    content_type,
+                    validators,
+                } => Fetched::Body {
+                    body: exec::run(command, Some(&body)).await?,
+                    content_type,
+                    validators,
+                },
+            };
+            Ok(FeedResponse {
+                moved_to: response
+                    .moved_to
+                    .map(|moved| format!("filter:{command}:{moved}")),
+                fetched,
+            })
+        }
+    }
+}
+
+/// Reads a feed file, using its modification time as the validator so an
+/// unchanged file counts as not modified.
+async fn fetch_file(url: &str, validators: &Validators) -> Result<FeedResponse> {
+    let path = Url::parse(url)
+        .ok()
+        .and_then(|u| u.to_file_path().ok())
+        .ok_or_else(|| Error::InvalidUrl(url.to_string()))?;
+    let read_error = |e| Error::FeedFile(path.display().to_string(), e);
+    let modified = tokio::fs::metadata(&path)
+        .await
+        .map_err(read_error)?
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|m| m.as_millis().to_string());
+    if modified.is_some() && modified == validators.last_modified {
+        return Ok(FeedResponse {
+            moved_to: None,
+            fetched: Fetched::NotModified,
+        });
+    }
+    let body = tokio::fs::read(&path).await.map_err(read_error)?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    Ok(FeedResponse {
+        moved_to: None,
+        fetched: Fetched::Body {
+            body,
+            content_type: is_json.then(|| "application/feed+json".to_string()),
+            validators: Validators {
+                etag: None,
+                last_modified: modified,
+            },
+        },
+    })
+}
+
+async fn fetch_http(url: &str, validators: &Validators) -> Result<FeedResponse> {
+    let start = Url::parse(url).map_err(|_| Error::InvalidUrl(url.to_string()))?;
+    let mut current = start.clone();
+    // Only a feed that was actually redirected has moved; the stored URL
+    // may merely be spelled differently from its parsed form.
+    let mut redirected = false;
+    let mut permanent = true;
+    for _ in 0..=MAX_REDIRECTS {
+        let mut req = FEED_CLIENT
+            .get(current.clone())
+            .header(ACCEPT, ACCEPT_FEEDS);
+        if let Some(etag) = &validators.etag {
+            req = req.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            req = req.header(IF_MODIFIED_SINCE, last_modified);
+        }
+
+        let resp = req.send().await?;
+        let status = resp.status();
+        if status.is_redirection() && status != StatusCode::NOT_MODIFIED {
+            let location =
+                header(resp.headers(), LOCATION.as_str()).ok_or(Error::Status(status.as_u16()))?;
+            current = current
+                .join(&location)
+                .map_err(|_| Error::InvalidUrl(location))?;
+            redirected = true;
+            permanent &= matches!(
+                status,
+                StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT
+            );
+            continue;
+        }
+
+        let moved_to = (redirected && permanent && current != start).then(|| current.to_string());
+        return Ok(FeedResponse {
+            moved_to,
+            fetched: read(resp).await?,
+        });
+    }
+    Err(Error::TooManyRedirects(url.to_string()))
+}
+
+async fn read(resp: Response) -> Result<Fetched> {
+    let status = resp.status();
+    if status == StatusCode::NOT_MODIFIED {
+        return Ok(Fetched::NotModified);
+    }
+    if status == StatusCode::GONE {
+        return Err(Error::Gone);
+    }
+    if matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+    ) {
+        return Err(Error::Throttled {
+            status: status.as_u16(),
+            retry_after: retry_after(resp.headers()),
+        });
+    }
+    if !status.is_success() {
+        return Err(Error::Status(status.as_u16()));
+    }
+
+    let headers = resp.headers().clone();
+    Ok(Fetched::Body {
+        body: resp.bytes().await?.to_vec(),
+        content_type: header(&headers, CONTENT_TYPE.as_str()),
+        validators: Validators {
+            etag: header(&headers, ETAG.as_str()),
+            last_modified: header(&headers, LAST_MODIFIED.as_str()),
+        },
+    })
+}
+
+/// `Retry-After` is either a number of seconds or an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = header(headers, RETRY_AFTER.as_str())?;
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
+fn header(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+/// A plain GET response; `url` is where the request ended up after redirects.
+pub struct Page {
+    pub url: String,
+    pub body: String,
+    pub content_type: Option<String>,
+}
+
+pub async fn get(url: &str) -> Result<Page> {
+    let resp = CLIENT.get(url).send().await?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(Error::Status(status.as_u16()));
+    }
+    let content_type = header(resp.headers(), CONTENT_TYPE.as_str());
+    let url = resp.url().to_string();
+    let bytes = resp.bytes().await?;
+    Ok(Page {
+        url,
+        body: charset::decode(&bytes, content_type.as_deref()),
+        content_type,
+    })
+}
+
+/// An image body and the type the server gave it.
+pub struct Image {
+    pub bytes: Vec<u8>,
+    pub content_type: Option<String>,
+}
+
+/// Fetches an image, giving up once the body passes `max_bytes`.
+pub async fn image(url: &str, max_bytes: usize) -> Result<Image> {
+    let mut resp = CLIENT.get(url).header(ACCEPT, "image/*").send().await?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(Error::Status(status.as_u16()));
+    }
+    let content_type = header(resp.headers(), CONTENT_TYPE.as_str());
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > max_bytes {
+            return Err(Error::TooLarge(url.to_string()));
+        }
+    }
+    Ok(Image {
+        bytes,
+        content_type,
+    })
+}
+
+/// Starts a media download, asking for the bytes after `offset` when part of
+/// the file is already on disk. The caller checks for 206 Partial Content.
+pub async fn download(url: &str, offset: u64) -> Result<Response> {
+    let mut req = CLIENT.get(url);
+    if offset > 0 {
+        req = req.header(RANGE, format!("bytes={offset}-"));
+    }
+    Ok(req.send().await?)
+}
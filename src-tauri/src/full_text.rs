@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use scraper::{ElementRef, Html, Selector};
+use tauri::{AppHandle, Emitter, Runtime};
+use url::Url;
+
+use crate::content;
+use crate::db;
+use crate::error::{Error, Result};
+use crate::fetch;
+use crate::html;
+
+/// Emitted after extracted articles are stored, so the reader can reload.
+pub const FULL_TEXT_UPDATED: &str = "full-text-updated";
+
+/// How many pages one pass fetches, so a newly enabled feed with a long
+/// backlog doesn't hog the connection.
+const BATCH: i64 = 10;
+/// Pages with less text than this are treated as a failed extraction.
+const MIN_TEXT_CHARS: usize = 250;
+
+const POSITIVE: &[&str] = &[
+    "article", "body", "content", "entry", "main", "page", "post", "story", "text",
+];
+const NEGATIVE: &[&str] = &[
+    "ad-",
+    "advert",
+    "comment",
+    "footer",
+    "footnote",
+    "masthead",
+    "meta",
+    "nav",
+    "newsletter",
+    "promo",
+    "related",
+    "share",
+    "sidebar",
+    "social",
+    "sponsor",
+    "subscribe",
+    "widget",
+];
+/// Elements dropped from the extracted content along with their children.
+const BOILERPLATE_TAGS: &[&str] = &["aside", "button", "footer", "form", "nav", "noscript"];
+
+/// Fetches and extracts articles from feeds that have full text enabled,
+/// a batch at a time.
+pub async fn extract_pending<R: Runtime>(app: &AppHandle<R>) -> Result<()> {
+    let pool = db::pool(app).await?;
+    let pending = db::list_pending_full_text(&pool, BATCH).await?;
+    if pending.is_empty() {
+        return Ok(());
+    }
+    for (article_id, link) in pending {
+        let result = fetch_and_extract(&link).await;
+        db::set_full_content(&pool, &article_id, &result).await?;
+    }
+    app.emit(FULL_TEXT_UPDATED, ())?;
+    Ok(())
+}
+
+/// Extracts one article now, replacing any earlier result or failure.
+pub async fn extract_article<R: Runtime>(app: &AppHandle<R>, article_id: &str) -> Result<String> {
+    let pool = db::pool(app).await?;
+    let link = db::article_link(&pool, article_id)
+        .await?
+        .ok_or_else(|| Error::ArticleNotFound(article_id.to_string()))?;
+    let result = fetch_and_extract(&link).await;
+    db::set_full_content(&pool, article_id, &result).await?;
+    result
+}
+
+async fn fetch_and_extract(link: &str) -> Result<String> {
+    let page = fetch::get(link).await?;
+    if page
+        .content_type
+        .as_deref()
+        .is_some_and(|ct| !ct.to_ascii_lowercase().contains("html"))
+    {
+        return Err(Error::NoArticleContent(link.to_string()));
+    }
+    let page_url = Url::parse(&page.url).map_err(|_| Error::InvalidUrl(page.url.clone()))?;
+    extract(&page.body, &page_url).ok_or_else(|| Error::NoArticleContent(link.to_string()))
+}
+
+/// Finds a page's main content, readability-style: an explicit article
+/// body if the page marks one, otherwise the element whose paragraphs
+/// score best. Returns it cleaned and ready to store.
+pub fn extract(page: &str, page_url: &Url) -> Option<String> {
+    let doc = Html::parse_document(page);
+    let best = marked_body(&doc).or_else(|| best_scored(&doc))?;
+    let main = html::remove_elements(&best.inner_html(), |element| {
+        BOILERPLATE_TAGS.contains(&&*element.name.local)
+            || class_weight(element.attr("class"), element.attr("id")) < 0.0
+    });
+    let main = content::prepare(&main, Some(page_url.as_str()));
+    let text = Html::parse_fragment(&main)
+        .root_element()
+        .text()
+        .map(str::trim)
+        .collect::<String>();
+    (text.chars().count() >= MIN_TEXT_CHARS).then_some(main)
+}
+
+/// `itemprop="articleBody"`, or the page's only `<article>`.
+fn marked_body(doc: &Html) -> Option<ElementRef<'_>> {
+    let long_enough = |el: &ElementRef| text_len(*el) >= MIN_TEXT_CHARS;
+    let article_body = Selector::parse(r#"[itemprop="articleBody"]"#).ok()?;
+    if let Some(body) = doc.select(&article_body).find(long_enough) {
+        return Some(body);
+    }
+    let articles = Selector::parse("article").ok()?;
+    let mut found = doc.select(&articles);
+    match (found.next(), found.next()) {
+        (Some(article), None) if long_enough(&article) => Some(article),
+        _ => None,
+    }
+}
+
+/// Scores each paragraph's parent and grandparent by the paragraph's
+/// length and commas, then discounts link-heavy candidates.
+fn best_scored(doc: &Html) -> Option<ElementRef<'_>> {
+    let paragraphs = Selector::parse("p, pre, td, blockquote").ok()?;
+    let mut scores: HashMap<_, f64> = HashMap::new();
+    for paragraph in doc.select(&paragraphs) {
+        let text: String = paragraph.text().collect();
+        let len = text.trim().chars().count();
+        if len < 25 || in_boilerplate(paragraph) {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (len / 100).min(3) as f64;
+        let parent = paragraph.parent().and_then(ElementRef::wrap);
+        let grandparent = parent.and_then(|p| p.parent()).and_then(ElementRef::wrap);
+        for (candidate, share) in [(parent, 1.0), (grandparent, 0.5)] {
+            if let Some(candidate) = candidate {
+                *scores
+                    .entry(candidate.id())
+                    .or_insert_with(|| initial_score(candidate)) += score * share;
+            }
+        }
+    }
+    scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let candidate = ElementRef::wrap(doc.tree.get(id)?)?;
+            Some((candidate, score * (1.0 - link_density(candidate))))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(candidate, _)| candidate)
+}
+
+fn initial_score(element: ElementRef) -> f64 {
+    let value = element.value();
+    let tag = match &*value.name.local {
+        "article" => 10.0,
+        "div" | "main" | "section" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "ol" | "ul" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    tag + class_weight(value.attr("class"), value.attr("id"))
+}
+
+fn class_weight(class: Option<&str>, id: Option<&str>) -> f64 {
+    [class, id]
+        .into_iter()
+        .flatten()
+        .map(|names| {
+            let names = names.to_ascii_lowercase();
+            if NEGATIVE.iter().any(|n| names.contains(n)) {
+                -25.0
+            } else if POSITIVE.iter().any(|p| names.contains(p)) {
+                25.0
+            } else {
+                0.0
+            }
+        })
+        .sum()
+}
+
+fn in_boilerplate(element: ElementRef) -> bool {
+    element.ancestors().filter_map(ElementRef::wrap).any(|a| {
+        BOILERPLATE_TAGS.contains(&&*a.value().name.local) || &*a.value().name.local == "header"
+    })
+}
+
+fn text_len(element: ElementRef) -> usize {
+    element.text().map(|t| t.trim().chars().count()).sum()
+}
+
+/// The share of an element's text that sits inside links.
+fn link_density(element: ElementRef) -> f64 {
+    let total = text_len(element);
+    if total == 0 {
+        return 1.0;
+    }
+    let Ok(links) = Selector::parse("a") else {
+        return 0.0;
+    };
+    let linked: usize = element.select(&links).map(text_len).sum();
+    linked as f64 / total as f64
+}
@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use sqlx::{Pool, Sqlite};
+
+use crate::db;
+use crate::error::{Error, Result};
+
+const BASE_BACKOFF: Duration = Duration::from_secs(5 * 60);
+const MAX_BACKOFF: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Updates the feed's error state after a refresh attempt. Failing feeds
+/// back off exponentially: 5 minutes, 10, 20, ... up to a day, or longer
+/// if the server sent a `Retry-After`.
+pub async fn record<T>(pool: &Pool<Sqlite>, url: &str, result: &Result<T>) -> Result<()> {
+    match result {
+        Ok(_) => db::record_success(pool, url).await,
+        Err(e) if e.is_feed_failure() => {
+            let failures = db::record_failure(pool, url, &e.to_string(), e.http_status()).await?;
+            let mut wait = backoff(failures);
+            if let Error::Throttled {
+                retry_after: Some(after),
+                ..
+            } = e
+            {
+                wait = wait.max(*after);
+            }
+            db::set_next_retry(pool, url, db::now_ms() + wait.as_millis() as i64).await
+        }
+        Err(_) => Ok(()),
+    }
+}
+
+fn backoff(failures: i64) -> Duration {
+    let doublings = failures.saturating_sub(1).clamp(0, 16) as u32;
+    BASE_BACKOFF.saturating_mul(1 << doublings).min(MAX_BACKOFF)
+}
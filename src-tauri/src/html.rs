@@ -0,0 +1,86 @@
+use scraper::Html;
+use scraper::node::{Element, Node};
+use url::Url;
+
+/// Parses an HTML fragment, hands every element to `edit`, and serializes
+/// the result.
+pub fn edit_elements(fragment: &str, mut edit: impl FnMut(&mut Element)) -> String {
+    let mut doc = Html::parse_fragment(fragment);
+    for node in doc.tree.values_mut() {
+        if let Node::Element(element) = node {
+            edit(element);
+        }
+    }
+    doc.root_element().inner_html()
+}
+
+/// Drops every element `remove` picks out, along with its contents.
+pub fn remove_elements(fragment: &str, remove: impl Fn(&Element) -> bool) -> String {
+    let mut doc = Html::parse_fragment(fragment);
+    let doomed: Vec<_> = doc
+        .tree
+        .nodes()
+        .filter(|node| matches!(node.value(), Node::Element(element) if remove(element)))
+        .map(|node| node.id())
+        .collect();
+    for id in doomed {
+        if let Some(mut node) = doc.tree.get_mut(id) {
+            node.detach();
+        }
+    }
+    doc.root_element().inner_html()
+}
+
+/// Attributes that hold a single URL.
+const URL_ATTRS: &[&str] = &["href", "src", "poster", "cite"];
+
+/// Makes every link, image and `srcset` candidate absolute against `base`.
+/// In-page anchors are left alone.
+pub fn resolve_urls(fragment: &str, base: &Url) -> String {
+    let resolve = |url: &str| {
+        let url = url.trim();
+        if url.starts_with('#') {
+            return url.to_string();
+        }
+        base.join(url)
+            .map_or_else(|_| url.to_string(), String::from)
+    };
+    edit_elements(fragment, |element| {
+        for name in URL_ATTRS {
+            if let Some(url) = element.attr(name).map(resolve) {
+                set_attr(element, name, url);
+            }
+        }
+        if let Some(srcset) = element.attr("srcset").map(|s| map_srcset(s, resolve)) {
+            set_attr(element, "srcset", srcset);
+        }
+    })
+}
+
+/// Replaces an attribute's value, leaving elements without it alone.
+pub fn set_attr(element: &mut Element, name: &str, value: String) {
+    if let Some((_, current)) = element
+        .attrs
+        .iter_mut()
+        .find(|(attr, _)| attr.local.as_ref() == name)
+    {
+        *current = value.into();
+    }
+}
+
+/// Applies `map` to every URL in a `srcset`, keeping the width and density
+/// descriptors.
+pub fn map_srcset(srcset: &str, mut map: impl FnMut(&str) -> String) -> String {
+    srcset
+        .split(',')
+        .map(str::trim)
+        .filter(|candidate| !candidate.is_empty())
+        .map(
+            |candidate| match candidate.split_once(char::is_whitespace) {
+                Some((url, descriptor)) => format!("{} {}", map(url), descriptor.trim()),
+                None => map(candidate),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use scraper::{Html, Selector};
+use tauri::{AppHandle, Emitter, Runtime};
+use url::Url;
+
+use crate::db::{self, FeedIcon, IconSource};
+use crate::error::Result;
+use crate::fetch;
+use crate::image_cache;
+
+/// Emitted when feeds get new icons, so the sidebar can reload them.
+pub const FEED_ICONS_UPDATED: &str = "feed-icons-updated";
+
+/// How long a found icon is kept before looking again.
+const FOUND_MAX_AGE_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+/// How long to wait before retrying a feed where no icon was found.
+const MISSING_RETRY_MS: i64 = 24 * 60 * 60 * 1000;
+const MAX_ICON_BYTES: usize = 1024 * 1024;
+
+/// Looks up icons for feeds that have none yet or whose icon is due for a
+/// refresh.
+pub async fn refresh_stale<R: Runtime>(app: &AppHandle<R>) -> Result<()> {
+    let pool = db::pool(app).await?;
+    let now = db::now_ms();
+    let stale = db::list_stale_icons(&pool, now - FOUND_MAX_AGE_MS, now - MISSING_RETRY_MS).await?;
+    let mut updated = false;
+    for feed in stale {
+        match resolve(&feed).await {
+            Some(icon) => {
+                db::save_feed_icon(&pool, &feed.url, &icon).await?;
+                updated = true;
+            }
+            None => db::mark_icon_checked(&pool, &feed.url).await?,
+        }
+    }
+    if updated {
+        app.emit(FEED_ICONS_UPDATED, ())?;
+    }
+    Ok(())
+}
+
+/// Addresses the webview can load each feed's icon from, keyed by feed URL.
+pub async fn addresses<R: Runtime>(app: &AppHandle<R>) -> Result<HashMap<String, String>> {
+    let pool = db::pool(app).await?;
+    Ok(db::list_feed_icons(&pool)
+        .await?
+        .into_iter()
+        .map(|(feed_url, fetched_at)| {
+            let version = fetched_at.to_string();
+            let address = image_cache::address("icon", &[("feed", &feed_url), ("v", &version)]);
+            (feed_url, address)
+        })
+        .collect())
+}
+
+/// Tries the feed's own icon, then the icons its site links to, then the
+/// site's /favicon.ico.
+async fn resolve(feed: &IconSource) -> Option<FeedIcon> {
+    let feed_url = Url::parse(&feed.url).ok()?;
+    let site = feed
+        .site_url
+        .as_deref()
+        .and_then(|s| feed_url.join(s).ok())
+        .filter(|s| matches!(s.scheme(), "http" | "https"))
+        .unwrap_or(feed_url);
+
+    if let Some(icon) = feed.icon_url.as_deref().and_then(|i| site.join(i).ok())
+        && let Some(found) = fetch_icon(icon.as_str()).await
+    {
+        return Some(found);
+    }
+    if let Ok(page) = fetch::get(site.as_str()).await {
+        let page_url = Url::parse(&page.url).unwrap_or_else(|_| site.clone());
+        for icon in page_icons(&page.body, &page_url) {
+            if let Some(found) = fetch_icon(&icon).await {
+                return Some(found);
+            }
+        }
+    }
+    fetch_icon(site.join("/favicon.ico").ok()?.as_str()).await
+}
+
+async fn fetch_icon(url: &str) -> Option<FeedIcon> {
+    let image = fetch::image(url, MAX_ICON_BYTES).await.ok()?;
+    // Servers often send favicon.ico as text/plain or octet-stream.
+    let content_type = image
+        .content_type
+        .filter(|ct| ct.to_ascii_lowercase().starts_with("image/"))
+        .or_else(|| sniff(&image.bytes).map(str::to_string))?;
+    Some(FeedIcon {
+        source_url: url.to_string(),
+        content_type,
+        data: image.bytes,
+    })
+}
+
+fn sniff(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0, 0, 1, 0]) {
+        Some("image/x-icon")
+    } else if bytes.starts_with(b"\x89PNG") {
+        Some("image/png")
+    } else if bytes.starts_with(b"GIF8") {
+        Some("image/gif")
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else {
+        None
+    }
+}
+
+/// Icons a page links to, resolved against it: `rel="icon"` and
+/// `rel="shortcut icon"` first, then the larger Apple touch icons.
+pub fn page_icons(html: &str, page_url: &Url) -> Vec<String> {
+    let doc = Html::parse_document(html);
+    let base = Selector::parse("base[href]")
+        .ok()
+        .and_then(|sel| doc.select(&sel).next())
+        .and_then(|el| el.value().attr("href"))
+        .and_then(|href| page_url.join(href).ok())
+        .unwrap_or_else(|| page_url.clone());
+
+    let Ok(links) = Selector::parse("link[rel][href]") else {
+        return Vec::new();
+    };
+    let mut icons: Vec<(bool, String)> = doc
+        .select(&links)
+        .filter_map(|el| {
+            let rel = el.value().attr("rel")?.to_ascii_lowercase();
+            let rels: Vec<&str> = rel.split_ascii_whitespace().collect();
+            let touch = rels.iter().any(|r| r.starts_with("apple-touch-icon"));
+            if !touch && !rels.contains(&"icon") {
+                return None;
+            }
+            let href = base.join(el.value().attr("href")?.trim()).ok()?;
+            Some((touch, href.to_string()))
+        })
+        .collect();
+    // A stable sort keeps document order within each group.
+    icons.sort_by_key(|(touch, _)| *touch);
+    icons.into_iter().map(|(_, href)| href).collect()
+}
@@ -0,0 +1,185 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use sqlx::{Pool, Sqlite};
+use tauri::http::{Request, Response, StatusCode, header};
+use tauri::{AppHandle, Manager, Runtime, UriSchemeContext, UriSchemeResponder};
+use url::Url;
+
+use crate::db;
+use crate::error::{Error, Result};
+use crate::feed::fnv1a;
+use crate::fetch;
+use crate::html;
+
+/// Article images are served from this scheme so the webview never talks to
+/// image hosts directly, and cached ones keep working offline.
+pub const SCHEME: &str = "lector-img";
+
+const LIMIT_KEY: &str = "image_cache_bytes";
+const DEFAULT_LIMIT: i64 = 256 * 1024 * 1024;
+/// Larger responses are passed over rather than cached.
+const MAX_IMAGE_BYTES: usize = 16 * 1024 * 1024;
+const DIR: &str = "images";
+
+static NEXT_PART: AtomicU64 = AtomicU64::new(0);
+
+/// Windows and Android webviews only load custom schemes through a
+/// `http://<scheme>.localhost` origin.
+#[cfg(any(windows, target_os = "android"))]
+const BASE: &str = "http://lector-img.localhost/";
+#[cfg(not(any(windows, target_os = "android")))]
+const BASE: &str = "lector-img://localhost/";
+
+/// The address the webview should load `url` from. Only absolute http(s)
+/// URLs can be fetched; anything else is returned as is.
+pub fn proxied(url: &str) -> String {
+    match Url::parse(url.trim()) {
+        Ok(parsed)
+            if matches!(parsed.scheme(), "http" | "https")
+                && !parsed.as_str().starts_with(BASE) =>
+        {
+            address("", &[("url", parsed.as_str())])
+        }
+        _ => url.to_string(),
+    }
+}
+
+/// Builds a `lector-img` address for `path` with the given query.
+pub(crate) fn address(path: &str, query: &[(&str, &str)]) -> String {
+    let mut address = Url::parse(BASE)
+        .and_then(|base| base.join(path))
+        .expect("valid image cache address");
+    address.query_pairs_mut().extend_pairs(query);
+    address.into()
+}
+
+/// Points an article's images, including `srcset` candidates, at the cache.
+pub fn rewrite(content: &str) -> String {
+    html::edit_elements(content, |element| {
+        if !matches!(&*element.name.local, "img" | "source") {
+            return;
+        }
+        if let Some(src) = element.attr("src").map(proxied) {
+            html::set_attr(element, "src", src);
+        }
+        if let Some(srcset) = element.attr("srcset").map(|s| html::map_srcset(s, proxied)) {
+            html::set_attr(element, "srcset", srcset);
+        }
+    })
+}
+
+/// Handles a `lector-img` request, answering from disk when the image is
+/// cached and fetching it otherwise.
+pub fn handle<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    let uri = request.uri().to_string();
+    tauri::async_runtime::spawn(async move {
+        let response = match serve(&app, &uri).await {
+            Ok((bytes, content_type)) => Response::builder()
+                .header(header::CONTENT_TYPE, content_type)
+                .header(header::CACHE_CONTROL, "max-age=31536000, immutable")
+                .body(bytes),
+            Err(e) => {
+                log::debug!("image cache: {uri}: {e}");
+                let status = match e {
+                    Error::InvalidUrl(_) => StatusCode::BAD_REQUEST,
+                    Error::FeedNotFound(_) => StatusCode::NOT_FOUND,
+                    Error::NotAnImage(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    _ => StatusCode::BAD_GATEWAY,
+                };
+                Response::builder().status(status).body(Vec::new())
+            }
+        };
+        responder.respond(response.expect("valid image response"));
+    });
+}
+
+async fn serve<R: Runtime>(app: &AppHandle<R>, uri: &str) -> Result<(Vec<u8>, String)> {
+    let parsed = Url::parse(uri).map_err(|_| Error::InvalidUrl(uri.to_string()))?;
+    let param = |name: &str| {
+        parsed
+            .query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+    };
+    let pool = db::pool(app).await?;
+
+    // Feed icons live in the database rather than the evictable cache.
+    if parsed.path() == "/icon" {
+        let feed = param("feed").ok_or_else(|| Error::InvalidUrl(uri.to_string()))?;
+        let icon = db::get_feed_icon(&pool, &feed)
+            .await?
+            .ok_or(Error::FeedNotFound(feed))?;
+        return Ok((icon.data, icon.content_type));
+    }
+
+    let url = param("url")
+        .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+        .ok_or_else(|| Error::InvalidUrl(uri.to_string()))?;
+    let dir = cache_dir(app)?;
+
+    let file = file_name(&url);
+    if let Some(content_type) = db::touch_cached_image(&pool, &url).await? {
+        match tokio::fs::read(dir.join(&file)).await {
+            Ok(bytes) => return Ok((bytes, content_type)),
+            // Deleted behind our back; fetch it again.
+            Err(_) => db::forget_cached_image(&pool, &url).await?,
+        }
+    }
+
+    let image = fetch::image(&url, MAX_IMAGE_BYTES).await?;
+    let content_type = image
+        .content_type
+        .filter(|ct| ct.to_ascii_lowercase().starts_with("image/"))
+        .ok_or_else(|| Error::NotAnImage(url.clone()))?;
+    tokio::fs::create_dir_all(&dir).await?;
+    // The same image is often requested twice at once (a thumbnail and the
+    // article body, or `src` and `srcset`), so each write gets its own
+    // temporary file and the renames just replace each other.
+    let part = dir.join(format!(
+        "{file}.{}.part",
+        NEXT_PART.fetch_add(1, Ordering::Relaxed)
+    ));
+    tokio::fs::write(&part, &image.bytes).await?;
+    tokio::fs::rename(&part, dir.join(&file)).await?;
+    db::insert_cached_image(&pool, &url, &file, &content_type, image.bytes.len() as i64).await?;
+    evict(app, &pool).await?;
+    Ok((image.bytes, content_type))
+}
+
+/// Sets the cache's size limit in bytes; `None` restores the default.
+pub async fn set_limit<R: Runtime>(app: &AppHandle<R>, bytes: Option<i64>) -> Result<()> {
+    let pool = db::pool(app).await?;
+    let value = bytes.filter(|b| *b > 0).map(|b| b.to_string());
+    db::set_meta(&pool, LIMIT_KEY, value.as_deref()).await?;
+    evict(app, &pool).await
+}
+
+/// Drops the least recently shown images until the cache fits its limit.
+async fn evict<R: Runtime>(app: &AppHandle<R>, pool: &Pool<Sqlite>) -> Result<()> {
+    let limit = db::get_meta(pool, LIMIT_KEY)
+        .await?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_LIMIT);
+    let dir = cache_dir(app)?;
+    for url in db::evict_cached_images(pool, limit).await? {
+        let _ = tokio::fs::remove_file(dir.join(file_name(&url))).await;
+    }
+    Ok(())
+}
+
+/// An image's file in the cache directory. Always derived from its URL:
+/// the webview can write the `image_cache` table, so a stored name could
+/// point anywhere on disk.
+fn file_name(url: &str) -> String {
+    format!("{:016x}", fnv1a(url.as_bytes()))
+}
+
+fn cache_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf> {
+    Ok(app.path().app_cache_dir()?.join(DIR))
+}
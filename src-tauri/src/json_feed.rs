@@ -0,0 +1,139 @@
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+use crate::feed::{Enclosure, Feed, FeedFormat, Item, Schedule};
+
+const VERSION_PREFIX: &str = "https://jsonfeed.org/version/";
+
+#[derive(Deserialize)]
+struct JsonFeed {
+    version: Option<String>,
+    title: Option<String>,
+    home_page_url: Option<String>,
+    description: Option<String>,
+    icon: Option<String>,
+    favicon: Option<String>,
+    // 1.0 has a single author; 1.1 deprecates it in favour of `authors`.
+    author: Option<Author>,
+    #[serde(default)]
+    authors: Vec<Author>,
+    #[serde(default)]
+    items: Vec<JsonItem>,
+}
+
+#[derive(Deserialize)]
+struct JsonItem {
+    // Required by the spec, but publishers send numbers as well as strings.
+    id: Option<serde_json::Value>,
+    url: Option<String>,
+    external_url: Option<String>,
+    title: Option<String>,
+    content_html: Option<String>,
+    content_text: Option<String>,
+    summary: Option<String>,
+    date_published: Option<String>,
+    date_modified: Option<String>,
+    image: Option<String>,
+    banner_image: Option<String>,
+    author: Option<Author>,
+    #[serde(default)]
+    authors: Vec<Author>,
+    #[serde(default)]
+    attachments: Vec<Attachment>,
+}
+
+#[derive(Deserialize)]
+struct Attachment {
+    url: Option<String>,
+    mime_type: Option<String>,
+    size_in_bytes: Option<f64>,
+    duration_in_seconds: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct Author {
+    name: Option<String>,
+}
+
+pub fn parse(text: &str) -> Result<Feed> {
+    let feed: JsonFeed = serde_json::from_str(text)?;
+    if !feed
+        .version
+        .as_deref()
+        .is_some_and(|v| v.starts_with(VERSION_PREFIX))
+    {
+        return Err(Error::UnsupportedFormat(
+            "JSON without a jsonfeed.org version".into(),
+        ));
+    }
+
+    let feed_author = author_name(feed.author, feed.authors);
+    let items = feed
+        .items
+        .into_iter()
+        .map(|item| Item {
+            guid: match item.id {
+                Some(serde_json::Value::String(id)) => non_empty(Some(id)),
+                Some(serde_json::Value::Number(id)) => Some(id.to_string()),
+                _ => None,
+            },
+            title: non_empty(item.title)
+                .or_else(|| non_empty(item.summary.clone()))
+                .unwrap_or_else(|| "Untitled".to_string()),
+            link: non_empty(item.url).or_else(|| non_empty(item.external_url)),
+            published: non_empty(item.date_published).or_else(|| non_empty(item.date_modified)),
+            content: non_empty(item.content_html)
+                .or_else(|| non_empty(item.content_text).map(|t| text_to_html(&t)))
+                .or_else(|| non_empty(item.summary).map(|t| text_to_html(&t))),
+            author: author_name(item.author, item.authors).or_else(|| feed_author.clone()),
+            enclosures: item
+                .attachments
+                .into_iter()
+                .filter_map(|a| {
+                    Some(Enclosure {
+                        url: non_empty(a.url)?,
+                        mime_type: non_empty(a.mime_type),
+                        length: a.size_in_bytes.map(|s| s as i64).filter(|s| *s > 0),
+                        duration: a.duration_in_seconds.map(|d| d as i64).filter(|d| *d > 0),
+                    })
+                })
+                .collect(),
+            image: non_empty(item.image).or_else(|| non_empty(item.banner_image)),
+            ..Item::default()
+        })
+        .collect();
+
+    Ok(Feed {
+        format: FeedFormat::JsonFeed,
+        title: non_empty(feed.title).unwrap_or_else(|| "Untitled".to_string()),
+        link: non_empty(feed.home_page_url),
+        description: non_empty(feed.description),
+        icon: non_empty(feed.favicon).or_else(|| non_empty(feed.icon)),
+        schedule: Schedule::default(),
+        items,
+        base: None,
+    })
+}
+
+fn author_name(author: Option<Author>, authors: Vec<Author>) -> Option<String> {
+    authors
+        .into_iter()
+        .chain(author)
+        .find_map(|a| non_empty(a.name))
+}
+
+/// `content_text` is plain text, but article content is rendered as HTML.
+fn text_to_html(text: &str) -> String {
+    let escaped = text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    escaped
+        .split("\n\n")
+        .map(|p| format!("<p>{}</p>", p.trim().replace('\n', "<br>")))
+        .collect()
+}
+
+fn non_empty(s: Option<String>) -> Option<String> {
+    s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
@@ -1,51 +1,89 @@
-use tauri_plugin_sql::{Migration, MigrationKind};
+mod charset;
+mod commands;
+pub mod content;
+pub mod date;
+pub mod db;
+mod directory;
+mod discover;
+mod downloads;
+pub mod error;
+pub mod exec;
+pub mod feed;
+mod fetch;
+pub mod full_text;
+mod health;
+mod html;
+pub mod icons;
+pub mod image_cache;
+mod json_feed;
+pub mod mail;
+mod refresh;
+mod revisions;
+pub mod sanitize;
+mod scheduler;
+pub mod scrape;
 
-pub fn run() {
-    let migrations = vec![
-        Migration {
-            version: 1,
-            description: "create_initial_tables",
-            sql: "CREATE TABLE IF NOT EXISTS feeds (
-                url TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                added_at INTEGER NOT NULL
-            );
-
-            CREATE TABLE IF NOT EXISTS articles (
-                id TEXT PRIMARY KEY,
-                feed_url TEXT NOT NULL REFERENCES feeds(url) ON DELETE CASCADE,
-                feed_name TEXT,
-                title TEXT NOT NULL,
-                link TEXT,
-                published TEXT,
-                published_ts INTEGER,
-                content TEXT,
-                author TEXT,
-                is_read INTEGER NOT NULL DEFAULT 0,
-                is_starred INTEGER NOT NULL DEFAULT 0,
-                fetched_at INTEGER NOT NULL
-            );
-
-            CREATE INDEX IF NOT EXISTS idx_articles_feed_url ON articles(feed_url);
-            CREATE INDEX IF NOT EXISTS idx_articles_published_ts ON articles(published_ts);
-            CREATE INDEX IF NOT EXISTS idx_articles_starred ON articles(is_starred);
-
-            CREATE TABLE IF NOT EXISTS meta (
-                key TEXT PRIMARY KEY,
-                value TEXT
-            );",
-            kind: MigrationKind::Up,
-        },
-    ];
+use db::DB_URL;
+use downloads::DownloadManager;
+use refresh::RefreshEngine;
+use tauri::Manager;
 
+pub fn run() {
     tauri::Builder::default()
+        // Background work has no one to return errors to; they go to
+        // stdout and the app's log directory.
+        .plugin(
+            tauri_plugin_log::Builder::new()
+                .level(log::LevelFilter::Info)
+                .build(),
+        )
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(
             tauri_plugin_sql::Builder::default()
-                .add_migrations("sqlite:lector.db", migrations)
+                .add_migrations(DB_URL, db::migrations())
                 .build(),
         )
+        .register_asynchronous_uri_scheme_protocol(image_cache::SCHEME, image_cache::handle)
+        .setup(|app| {
+            exec::init(&app.path().app_config_dir()?);
+            app.manage(RefreshEngine::default());
+            app.manage(DownloadManager::default());
+            scheduler::start(app.handle().clone());
+            downloads::start(app.handle().clone());
+            content::start(app.handle().clone());
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            commands::parse_feed,
+            commands::refresh_feed,
+            commands::refresh_all_feeds,
+            commands::refresh_due_feeds,
+            commands::set_refresh_interval,
+            commands::set_feed_directory,
+            commands::add_mailbox,
+            commands::remove_mailbox,
+            commands::list_mailboxes,
+            commands::discover_feeds,
+            commands::list_unhealthy_feeds,
+            commands::get_article_revisions,
+            commands::diff_article_revisions,
+            commands::add_feed,
+            commands::add_scraper_feed,
+            commands::preview_scraper_feed,
+            commands::list_episodes,
+            commands::download_enclosure,
+            commands::delete_download,
+            commands::list_downloads,
+            commands::mark_episode_played,
+            commands::set_auto_download,
+            commands::set_full_text,
+            commands::extract_article,
+            commands::set_download_quota,
+            commands::set_image_cache_limit,
+            commands::list_feed_icons,
+            commands::prepare_content
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,223 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use mail_parser::MessageParser;
+use mail_parser::mailbox::{maildir, mbox};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+
+use crate::db::{self, Mailbox};
+use crate::error::{Error, Result};
+use crate::feed::{self, Item};
+use crate::scheduler::ArticlesUpdated;
+
+/// Newsletter feeds are keyed by sender under this scheme, one feed per
+/// address.
+pub const SCHEME: &str = "mailto:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum MailboxKind {
+    Maildir,
+    Mbox,
+}
+
+/// One newsletter issue, ready to store in its sender's feed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Newsletter {
+    pub message_id: String,
+    /// The sender's address, lowercased.
+    pub sender: String,
+    pub sender_name: Option<String>,
+    pub item: Item,
+}
+
+/// Starts importing a Maildir (a directory with `cur` and `new`) or an
+/// mbox file, and imports what it holds right away.
+pub async fn add_mailbox(pool: &Pool<Sqlite>, path: &str) -> Result<ArticlesUpdated> {
+    let path = path.trim();
+    let read_error = |e| Error::FeedFile(path.to_string(), e);
+    let canonical = tokio::fs::canonicalize(path).await.map_err(read_error)?;
+    let kind = if canonical.is_dir() {
+        if !canonical.join("cur").is_dir() || !canonical.join("new").is_dir() {
+            return Err(read_error(std::io::ErrorKind::NotADirectory.into()));
+        }
+        MailboxKind::Maildir
+    } else {
+        MailboxKind::Mbox
+    };
+    let canonical = canonical
+        .to_str()
+        .ok_or_else(|| Error::InvalidUrl(path.to_string()))?;
+    db::insert_mailbox(pool, canonical, kind).await?;
+    sync(pool).await
+}
+
+/// Imports messages that arrived in any mailbox since the last pass.
+/// Mailboxes whose modification time hasn't moved are skipped, and
+/// messages already imported are recognised by their Message-ID.
+pub async fn sync(pool: &Pool<Sqlite>) -> Result<ArticlesUpdated> {
+    let mut update = ArticlesUpdated::default();
+    let mailboxes = db::list_mailboxes(pool).await?;
+    if mailboxes.is_empty() {
+        return Ok(update);
+    }
+    let mut consumed = db::consumed_message_ids(pool).await?;
+    for mailbox in mailboxes {
+        // One missing or unreadable mailbox shouldn't hold up the others,
+        // and its error is kept for the settings screen.
+        if let Err(e) = sync_mailbox(pool, &mailbox, &mut consumed, &mut update).await {
+            db::record_mailbox_failure(pool, &mailbox.path, &e.to_string()).await?;
+        }
+    }
+    Ok(update)
+}
+
+async fn sync_mailbox(
+    pool: &Pool<Sqlite>,
+    mailbox: &Mailbox,
+    consumed: &mut HashSet<String>,
+    update: &mut ArticlesUpdated,
+) -> Result<()> {
+    let modified = modified_at(mailbox).await?;
+    if modified.is_some() && modified == mailbox.modified_at {
+        return Ok(());
+    }
+    let path = mailbox.path.clone();
+    let kind = mailbox.kind;
+    // Parsed as they are read, so only the newsletters are held in memory,
+    // not the whole mailbox.
+    let newsletters = tokio::task::spawn_blocking(move || {
+        let mut newsletters = Vec::new();
+        read_mailbox(Path::new(&path), kind, |raw| match parse_message(&raw) {
+            Some(newsletter) => newsletters.push(newsletter),
+            None => log::warn!("skipping a message without a sender in {path}"),
+        })?;
+        Ok::<_, Error>(newsletters)
+    })
+    .await
+    .map_err(|e| Error::FeedFile(mailbox.path.clone(), std::io::Error::other(e)))??;
+
+    let mut by_sender: BTreeMap<String, Vec<Newsletter>> = BTreeMap::new();
+    for newsletter in newsletters {
+        if !consumed.contains(&newsletter.message_id) {
+            by_sender
+                .entry(newsletter.sender.clone())
+                .or_default()
+                .push(newsletter);
+        }
+    }
+    for (sender, newsletters) in by_sender {
+        let url = format!("{SCHEME}{sender}");
+        let name = newsletters
+            .iter()
+            .find_map(|n| n.sender_name.as_deref())
+            .unwrap_or(&sender);
+        let items: Vec<Item> = newsletters.iter().map(|n| n.item.clone()).collect();
+        let ids: Vec<&str> = newsletters.iter().map(|n| n.message_id.as_str()).collect();
+        let new_items = db::store_newsletters(pool, &url, name, &items, &ids).await?;
+        consumed.extend(ids.into_iter().map(str::to_string));
+        if new_items > 0 {
+            update.new_articles += new_items;
+            update.feed_urls.push(url);
+        }
+    }
+    db::mark_mailbox_synced(pool, &mailbox.path, modified).await
+}
+
+/// An mbox's modification time, or for a Maildir the latest of its `cur`
+/// and `new` directories, which change whenever a message is delivered or
+/// moved.
+async fn modified_at(mailbox: &Mailbox) -> Result<Option<i64>> {
+    let path = Path::new(&mailbox.path);
+    let watched = match mailbox.kind {
+        MailboxKind::Mbox => vec![path.to_path_buf()],
+        MailboxKind::Maildir => vec![path.join("cur"), path.join("new")],
+    };
+    let mut latest = None;
+    for path in watched {
+        let meta = tokio::fs::metadata(&path)
+            .await
+            .map_err(|e| Error::FeedFile(path.display().to_string(), e))?;
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .map(|m| m.as_millis() as i64);
+        latest = latest.max(modified);
+    }
+    Ok(latest)
+}
+
+/// Hands each raw message in a mailbox to `each`, one at a time. A Maildir
+/// message that can't be read is logged and skipped; a read error in an
+/// mbox leaves the rest of the file unreachable, so it fails the import
+/// and the next sync tries again. Blocking.
+pub fn read_mailbox(path: &Path, kind: MailboxKind, mut each: impl FnMut(Vec<u8>)) -> Result<()> {
+    let read_error = |e| Error::FeedFile(path.display().to_string(), e);
+    match kind {
+        MailboxKind::Maildir => {
+            for message in maildir::MessageIterator::new(path).map_err(read_error)? {
+                match message {
+                    Ok(message) => each(message.unwrap_contents()),
+                    Err(e) => log::warn!("skipping a message in {}: {e}", path.display()),
+                }
+            }
+        }
+        MailboxKind::Mbox => {
+            let file = File::open(path).map_err(read_error)?;
+            for message in mbox::MessageIterator::new(BufReader::new(file)) {
+                each(message.map_err(read_error)?.unwrap_contents());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Turns a raw message into a newsletter issue: the subject is the title
+/// and the HTML body (or the text body, as HTML) the content. Messages
+/// without a sender are skipped.
+pub fn parse_message(raw: &[u8]) -> Option<Newsletter> {
+    let message = MessageParser::default().parse(raw)?;
+    let from = message.from()?.first()?;
+    let sender = from.address()?.trim().to_ascii_lowercase();
+    if sender.is_empty() {
+        return None;
+    }
+    let sender_name = from
+        .name()
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(str::to_string);
+    // Messages without a Message-ID still need a stable identity.
+    let message_id = message
+        .message_id()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("hash:{:016x}", feed::fnv1a(raw)));
+
+    let mut items = [Item {
+        guid: Some(message_id.clone()),
+        title: message
+            .subject()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or("Untitled")
+            .to_string(),
+        published: message.date().map(|d| d.to_rfc3339()),
+        content: message.body_html(0).map(|body| body.into_owned()),
+        author: sender_name.clone().or_else(|| Some(sender.clone())),
+        ..Item::default()
+    }];
+    feed::fill_in_items(&mut items);
+    let [item] = items;
+    Some(Newsletter {
+        message_id,
+        sender,
+        sender_name,
+        item,
+    })
+}
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use url::Url;
+
+use crate::charset;
+use crate::db;
+use crate::error::{Error, Result};
+use crate::exec::{self, Source};
+use crate::feed;
+use crate::fetch::{self, Fetched, Validators};
+use crate::health;
+use crate::scrape::{self, Selectors};
+
+const MAX_CONCURRENCY_KEY: &str = "refresh_max_concurrency";
+const PER_HOST_CONCURRENCY_KEY: &str = "refresh_per_host_concurrency";
+const PER_HOST_DELAY_KEY: &str = "refresh_per_host_delay_ms";
+
+const DEFAULT_MAX_CONCURRENCY: usize = 8;
+const DEFAULT_PER_HOST_CONCURRENCY: usize = 2;
+const DEFAULT_PER_HOST_DELAY: Duration = Duration::from_millis(1000);
+
+/// Used when a 429/503 response doesn't say how long to wait.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+/// A host paused for longer than this fails its feeds for this pass instead
+/// of holding the whole refresh up.
+const MAX_HOST_WAIT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshOutcome {
+    pub url: String,
+    pub not_modified: bool,
+    pub item_count: usize,
+    pub new_items: usize,
+}
+
+/// Concurrency limits, read from the `meta` table at the start of each pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub max_concurrency: usize,
+    pub per_host_concurrency: usize,
+    pub per_host_delay: Duration,
+}
+
+impl Limits {
+    pub async fn load(pool: &Pool<Sqlite>) -> Result<Limits> {
+        let number = |v: Option<String>| v.and_then(|v| v.trim().parse::<u64>().ok());
+        let max_concurrency = number(db::get_meta(pool, MAX_CONCURRENCY_KEY).await?)
+            .filter(|n| *n > 0)
+            .map_or(DEFAULT_MAX_CONCURRENCY, |n| n as usize);
+        let per_host_concurrency = number(db::get_meta(pool, PER_HOST_CONCURRENCY_KEY).await?)
+            .filter(|n| *n > 0)
+            .map_or(DEFAULT_PER_HOST_CONCURRENCY, |n| n as usize);
+        let per_host_delay = number(db::get_meta(pool, PER_HOST_DELAY_KEY).await?)
+            .map_or(DEFAULT_PER_HOST_DELAY, Duration::from_millis);
+        Ok(Limits {
+            max_concurrency,
+            per_host_concurrency,
+            per_host_delay,
+        })
+    }
+}
+
+struct Host {
+    permits: Semaphore,
+    /// Earliest moment the next request to this host may start.
+    next_at: Mutex<Instant>,
+}
+
+/// Runs refreshes with a global concurrency limit plus a per-host limit and
+/// delay. Hosts are remembered across passes so a `Retry-After` outlives
+/// the pass that received it.
+#[derive(Clone, Default)]
+pub struct RefreshEngine {
+    hosts: Arc<Mutex<Hosts>>,
+}
+
+#[derive(Default)]
+struct Hosts {
+    limits: Option<Limits>,
+    by_name: HashMap<String, Arc<Host>>,
+}
+
+impl RefreshEngine {
+    pub async fn refresh_all(
+        &self,
+        pool: &Pool<Sqlite>,
+        urls: Vec<String>,
+    ) -> Result<Vec<(String, Result<RefreshOutcome>)>> {
+        let limits = Limits::load(pool).await?;
+        let global = Arc::new(Semaphore::new(limits.max_concurrency));
+        let mut tasks = JoinSet::new();
+        let mut by_task = HashMap::new();
+        for url in urls {
+            let engine = self.clone();
+            let pool = pool.clone();
+            let global = global.clone();
+            let task = tasks.spawn({
+                let url = url.clone();
+                async move {
+                    let result = engine.refresh_one(&pool, &url, limits, &global).await;
+                    (url, result)
+                }
+            });
+            by_task.insert(task.id(), url);
+        }
+
+        let mut results = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok(result) => results.push(result),
+                // A panicked refresh still answers for its feed.
+                Err(e) => {
+                    if let Some(url) = by_task.remove(&e.id()) {
+                        results.push((url, Err(e.into())));
+                    }
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Waits for the feed's host before taking a global slot, so feeds
+    /// queued behind one busy host don't keep other hosts idle.
+    async fn refresh_one(
+        &self,
+        pool: &Pool<Sqlite>,
+        url: &str,
+        limits: Limits,
+        global: &Semaphore,
+    ) -> Result<RefreshOutcome> {
+        // Local files and commands have no server to spare.
+        if matches!(
+            exec::source(url),
+            Source::File(_) | Source::Exec(_) | Source::Mail(_)
+        ) {
+            let _permit = global.acquire().await;
+            let result = refresh_feed(pool, url).await;
+            record_health(pool, url, &result).await;
+            return result;
+        }
+        let host = self.host(url, limits);
+        let _permit = host.permits.acquire().await;
+
+        let start = {
+            let mut next_at = host.next_at.lock().unwrap();
+            let now = Instant::now();
+            if next_at.saturating_duration_since(now) > MAX_HOST_WAIT {
+                return Err(Error::HostPaused(next_at.saturating_duration_since(now)));
+            }
+            let start = (*next_at).max(now);
+            *next_at = start + limits.per_host_delay;
+            start
+        };
+        tokio::time::sleep_until(start).await;
+
+        let _permit = global.acquire().await;
+        let result = refresh_feed(pool, url).await;
+        record_health(pool, url, &result).await;
+        if let Err(Error::Throttled { retry_after, .. }) = &result {
+            let wait = retry_after
+                .unwrap_or(DEFAULT_RETRY_AFTER)
+                .min(MAX_RETRY_AFTER);
+            let mut next_at = host.next_at.lock().unwrap();
+            *next_at = (*next_at).max(Instant::now() + wait);
+        }
+        result
+    }
+
+    fn host(&self, url: &str, limits: Limits) -> Arc<Host> {
+        let key = Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+            .unwrap_or_default();
+        let mut hosts = self.hosts.lock().unwrap();
+        // Semaphores can't be resized, so swap them out when the limits
+        // change, keeping any pause a host asked for.
+        if hosts.limits != Some(limits) {
+            hosts.limits = Some(limits);
+            for host in hosts.by_name.values_mut() {
+                let next_at = *host.next_at.lock().unwrap();
+                *host = Arc::new(Host {
+                    permits: Semaphore::new(limits.per_host_concurrency),
+                    next_at: Mutex::new(next_at),
+                });
+            }
+        }
+        hosts
+            .by_name
+            .entry(key)
+            .or_insert_with(|| {
+                Arc::new(Host {
+                    permits: Semaphore::new(limits.per_host_concurrency),
+                    next_at: Mutex::new(Instant::now()),
+                })
+            })
+            .clone()
+    }
+}
+
+async fn record_health(pool: &Pool<Sqlite>, url: &str, result: &Result<RefreshOutcome>) {
+    // The feed may have moved during the refresh.
+    let current_url = result.as_ref().map_or(url, |outcome| outcome.url.as_str());
+    if let Err(e) = health::record(pool, current_url, result).await {
+        log::warn!("could not record health of {url}: {e}");
+    }
+}
+
+/// Fetches one subscribed feed, skipping parse and upsert when the server
+/// answers 304 Not Modified. A permanent redirect moves the subscription to
+/// the new URL; 410 Gone retires it.
+async fn refresh_feed(pool: &Pool<Sqlite>, url: &str) -> Result<RefreshOutcome> {
+    let row = db::get_feed(pool, url).await?;
+    let selectors = db::get_scraper_selectors(pool, &row.url).await?;
+    let validators = Validators {
+        etag: row.etag,
+        last_modified: row.last_modified,
+    };
+
+    let allow_commands = exec::enabled().await;
+    let response = match fetch::fetch(&row.url, &validators, allow_commands).await {
+        Err(Error::Gone) => {
+            db::retire_feed(pool, &row.url).await?;
+            return Err(Error::Gone);
+        }
+        response => response?,
+    };
+    let url = match response.moved_to {
+        Some(new_url) => {
+            db::move_feed(pool, &row.url, &new_url).await?;
+            new_url
+        }
+        None => row.url,
+    };
+
+    match response.fetched {
+        Fetched::NotModified => {
+            db::touch_fetched(pool, &url).await?;
+            Ok(RefreshOutcome {
+                url,
+                not_modified: true,
+                item_count: 0,
+                new_items: 0,
+            })
+        }
+        Fetched::Body {
+            body,
+            content_type,
+            validators,
+        } => {
+            let parsed = parse(&body, content_type.as_deref(), &url, selectors.as_ref())?;
+            store(pool, url, &row.name, &parsed, &validators).await
+        }
+    }
+}
+
+/// Fetches a feed for the first time and subscribes to it, under `name` or
+/// the feed's own title. A permanent redirect subscribes to where it led.
+/// With `selectors` the URL is an HTML page to scrape instead of a feed.
+pub async fn subscribe(
+    pool: &Pool<Sqlite>,
+    url: &str,
+    name: Option<&str>,
+    selectors: Option<&Selectors>,
+) -> Result<RefreshOutcome> {
+    let allow_commands = exec::enabled().await;
+    let response = fetch::fetch(url, &Validators::default(), allow_commands).await?;
+    let url = response.moved_to.unwrap_or_else(|| url.to_string());
+    let Fetched::Body {
+        body,
+        content_type,
+        validators,
+    } = response.fetched
+    else {
+        // Only possible if the server ignores the missing validators.
+        return Err(Error::Status(304));
+    };
+    let parsed = parse(&body, content_type.as_deref(), &url, selectors)?;
+    let name = name
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .unwrap_or(&parsed.title);
+    db::insert_feed(pool, &url, name).await?;
+    if let Some(selectors) = selectors {
+        db::save_scraper_selectors(pool, &url, selectors).await?;
+    }
+    store(pool, url.clone(), name, &parsed, &validators).await
+}
+
+fn parse(
+    body: &[u8],
+    content_type: Option<&str>,
+    url: &str,
+    selectors: Option<&Selectors>,
+) -> Result<feed::Feed> {
+    let mut parsed = match selectors {
+        Some(selectors) => scrape::parse(&charset::decode(body, content_type), selectors)?,
+        None => feed::parse_bytes(body, content_type)?,
+    };
+    parsed.resolve_urls(exec::document_url(url));
+    Ok(parsed)
+}
+
+async fn store(
+    pool: &Pool<Sqlite>,
+    url: String,
+    name: &str,
+    parsed: &feed::Feed,
+    validators: &Validators,
+) -> Result<RefreshOutcome> {
+    let new_items = db::upsert_articles(pool, &url, name, &parsed.items).await?;
+    db::update_schedule(pool, &url, &parsed.schedule).await?;
+    db::update_feed_links(pool, &url, parsed.link.as_deref(), parsed.icon.as_deref()).await?;
+    db::mark_fetched(
+        pool,
+        &url,
+        validators.etag.as_deref(),
+        validators.last_modified.as_deref(),
+    )
+    .await?;
+    Ok(RefreshOutcome {
+        url,
+        not_modified: false,
+        item_count: parsed.items.len(),
+        new_items,
+    })
+}
@@ -0,0 +1,76 @@
+use scraper::Html;
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+
+use crate::feed;
+
+/// One version of an article. The current version has no `id`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Revision {
+    pub id: Option<i64>,
+    pub title: String,
+    pub content: Option<String>,
+    /// When a newer version replaced this one.
+    pub replaced_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffKind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffChunk {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+/// What a revision is keyed on: a change to either the title or the body
+/// counts as an edit.
+pub fn content_hash(title: &str, content: Option<&str>) -> String {
+    let key = format!("{title}\n{}", content.unwrap_or_default());
+    format!("{:016x}", feed::fnv1a(key.as_bytes()))
+}
+
+/// Word-level diff of the visible text of two revisions, title first.
+pub fn diff(from: &Revision, to: &Revision) -> Vec<DiffChunk> {
+    let old = plain_text(from);
+    let new = plain_text(to);
+    let mut chunks: Vec<DiffChunk> = Vec::new();
+    for change in TextDiff::from_words(&old, &new).iter_all_changes() {
+        let kind = match change.tag() {
+            ChangeTag::Equal => DiffKind::Equal,
+            ChangeTag::Insert => DiffKind::Insert,
+            ChangeTag::Delete => DiffKind::Delete,
+        };
+        match chunks.last_mut() {
+            Some(last) if last.kind == kind => last.text.push_str(change.value()),
+            _ => chunks.push(DiffChunk {
+                kind,
+                text: change.value().to_string(),
+            }),
+        }
+    }
+    chunks
+}
+
+fn plain_text(revision: &Revision) -> String {
+    let body = revision
+        .content
+        .as_deref()
+        .map(|html| {
+            let fragment = Html::parse_fragment(html);
+            fragment
+                .root_element()
+                .text()
+                .flat_map(str::split_whitespace)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+    format!("{}\n\n{body}", revision.title)
+}
@@ -0,0 +1,63 @@
+use std::sync::LazyLock;
+
+use ammonia::Builder;
+use url::Url;
+
+use crate::html;
+
+/// Hosts whose embeds are kept; any other iframe is removed.
+const EMBED_HOSTS: &[&str] = &[
+    "www.youtube.com",
+    "www.youtube-nocookie.com",
+    "player.vimeo.com",
+    "w.soundcloud.com",
+    "open.spotify.com",
+    "embed.podcasts.apple.com",
+    "bandcamp.com",
+];
+
+/// Ammonia's defaults (no scripts, styles, event handlers or `javascript:`
+/// URLs) plus the media elements feeds use.
+static CLEANER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::default();
+    builder
+        .add_tags(["audio", "iframe", "picture", "source", "track", "video"])
+        .add_tag_attributes("img", ["srcset", "sizes", "loading"])
+        .add_tag_attributes("source", ["src", "srcset", "sizes", "media", "type"])
+        .add_tag_attributes("track", ["src", "kind", "label", "srclang"])
+        .add_tag_attributes(
+            "video",
+            [
+                "src",
+                "poster",
+                "controls",
+                "width",
+                "height",
+                "loop",
+                "muted",
+                "playsinline",
+            ],
+        )
+        .add_tag_attributes("audio", ["src", "controls", "loop"])
+        .add_tag_attributes("iframe", ["src", "width", "height", "allowfullscreen"])
+        // Stored content can already point at the image cache.
+        .add_url_schemes(["lector-img"]);
+    builder
+});
+
+/// Reduces feed HTML to markup that is safe to render in the app's webview.
+pub fn clean(content: &str) -> String {
+    let content = html::remove_elements(content, |element| {
+        &*element.name.local == "iframe" && !element.attr("src").is_some_and(is_known_embed)
+    });
+    CLEANER.clean(&content).to_string()
+}
+
+fn is_known_embed(src: &str) -> bool {
+    Url::parse(src.trim()).is_ok_and(|url| {
+        url.scheme() == "https"
+            && url
+                .host_str()
+                .is_some_and(|host| EMBED_HOSTS.contains(&host))
+    })
+}
@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::db::{self, FeedSchedule};
+use crate::directory;
+use crate::downloads;
+use crate::error::Result;
+use crate::feed::DAY_NAMES;
+use crate::full_text;
+use crate::icons;
+use crate::mail;
+use crate::refresh::{RefreshEngine, RefreshOutcome};
+
+/// Emitted to the webview whenever a background pass stores new articles.
+pub const ARTICLES_UPDATED: &str = "articles-updated";
+
+/// How often the loop wakes up to look for feeds that are due.
+const TICK: Duration = Duration::from_secs(60);
+const DEFAULT_INTERVAL_MINUTES: i64 = 30;
+const INTERVAL_KEY: &str = "refresh_interval_minutes";
+
+/// Bounds applied to what feeds declare; a user override is taken as-is.
+const MIN_DECLARED_MINUTES: i64 = 15;
+const MAX_DECLARED_MINUTES: i64 = 60 * 24;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticlesUpdated {
+    pub feed_urls: Vec<String>,
+    pub new_articles: usize,
+}
+
+/// Starts the background refresh loop.
+pub fn start<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TICK).await;
+            if let Err(e) = tick(&app).await {
+                log::warn!("background refresh failed: {e}");
+            }
+        }
+    });
+}
+
+async fn tick<R: Runtime>(app: &AppHandle<R>) -> Result<()> {
+    let pool = db::pool(app).await?;
+    let engine = app.state::<RefreshEngine>();
+    let mut update = refresh_due(&pool, &engine).await?;
+    match directory::sync(&pool, &engine).await {
+        Ok(local) => {
+            update.new_articles += local.new_articles;
+            update.feed_urls.extend(local.feed_urls);
+        }
+        Err(e) => log::warn!("could not sync the feed directory: {e}"),
+    }
+    match mail::sync(&pool).await {
+        Ok(mail) => {
+            update.new_articles += mail.new_articles;
+            update.feed_urls.extend(mail.feed_urls);
+        }
+        Err(e) => log::warn!("could not import newsletters: {e}"),
+    }
+    if update.new_articles > 0 {
+        downloads::apply_rules(app).await?;
+        app.emit(ARTICLES_UPDATED, update)?;
+    }
+    if let Err(e) = icons::refresh_stale(app).await {
+        log::warn!("could not refresh feed icons: {e}");
+    }
+    if let Err(e) = full_text::extract_pending(app).await {
+        log::warn!("could not extract full text: {e}");
+    }
+    Ok(())
+}
+
+/// Refreshes every feed whose interval has elapsed and that isn't inside
+/// one of its declared skip hours or days.
+pub async fn refresh_due(pool: &Pool<Sqlite>, engine: &RefreshEngine) -> Result<ArticlesUpdated> {
+    let default_minutes = db::get_meta(pool, INTERVAL_KEY)
+        .await?
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|m| *m > 0)
+        .unwrap_or(DEFAULT_INTERVAL_MINUTES);
+    let now = Utc::now();
+
+    let due = db::list_feed_schedules(pool)
+        .await?
+        .into_iter()
+        .filter(|feed| is_due(feed, default_minutes, now))
+        .map(|feed| feed.url)
+        .collect();
+    Ok(summarize(engine.refresh_all(pool, due).await?))
+}
+
+pub fn summarize(results: Vec<(String, Result<RefreshOutcome>)>) -> ArticlesUpdated {
+    let mut update = ArticlesUpdated {
+        feed_urls: Vec::new(),
+        new_articles: 0,
+    };
+    for (url, result) in results {
+        match result {
+            Ok(outcome) if outcome.new_items > 0 => {
+                update.new_articles += outcome.new_items;
+                update.feed_urls.push(outcome.url);
+            }
+            Ok(_) => {}
+            // Feed failures are already on the feed's health row.
+            Err(e) if e.is_feed_failure() => log::debug!("refresh of {url} failed: {e}"),
+            Err(e) => log::warn!("refresh of {url} failed: {e}"),
+        }
+    }
+    update
+}
+
+fn is_due(feed: &FeedSchedule, default_minutes: i64, now: DateTime<Utc>) -> bool {
+    if feed
+        .next_retry_at
+        .is_some_and(|at| at > now.timestamp_millis())
+    {
+        return false;
+    }
+    let Some(last_fetched_at) = feed.last_fetched_at else {
+        return true;
+    };
+    if feed.refresh_interval_override.is_none() {
+        let hour = now.hour() as u8;
+        let day = DAY_NAMES[now.weekday().num_days_from_monday() as usize];
+        if feed.skip_hours.contains(&hour) || feed.skip_days.iter().any(|d| d == day) {
+            return false;
+        }
+    }
+    let minutes = feed
+        .refresh_interval_override
+        .filter(|m| *m > 0)
+        .or_else(|| {
+            feed.refresh_interval
+                .map(|m| m.clamp(MIN_DECLARED_MINUTES, MAX_DECLARED_MINUTES))
+        })
+        .unwrap_or(default_minutes);
+    now.timestamp_millis() - last_fetched_at >= minutes * 60_000
+}
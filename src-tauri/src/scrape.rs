@@ -0,0 +1,145 @@
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::feed::{self, Feed, FeedFormat, Item, Schedule};
+use crate::fetch;
+
+/// CSS selectors that turn an HTML page into feed items. `item` picks out
+/// one element per item; the rest are matched inside it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Selectors {
+    #[sqlx(rename = "item_selector")]
+    pub item: String,
+    /// Defaults to the first heading, then the link's text.
+    #[sqlx(rename = "title_selector")]
+    pub title: Option<String>,
+    /// Defaults to the item itself when it is a link, then its first link.
+    #[sqlx(rename = "link_selector")]
+    pub link: Option<String>,
+    /// Read from a `datetime` or `content` attribute if there is one,
+    /// otherwise from the text.
+    #[sqlx(rename = "date_selector")]
+    pub date: Option<String>,
+    #[sqlx(rename = "content_selector")]
+    pub content: Option<String>,
+}
+
+/// Fetches a page and scrapes it without subscribing, so selectors can be
+/// tried out first.
+pub async fn preview(url: &str, selectors: &Selectors) -> Result<Feed> {
+    let page = fetch::get(url).await?;
+    let mut feed = parse(&page.body, selectors)?;
+    feed.resolve_urls(&page.url);
+    Ok(feed)
+}
+
+/// Builds a feed from a page. URLs are left as they appear on the page;
+/// `Feed::resolve_urls` makes them absolute against the page's `<base>` or
+/// address.
+pub fn parse(page: &str, selectors: &Selectors) -> Result<Feed> {
+    let compile = |s: &str| Selector::parse(s).map_err(|_| Error::InvalidSelector(s.to_string()));
+    let compile_optional = |s: &Option<String>| {
+        s.as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(compile)
+            .transpose()
+    };
+    let item = compile(selectors.item.trim())?;
+    let title = compile_optional(&selectors.title)?;
+    let link = compile_optional(&selectors.link)?;
+    let date = compile_optional(&selectors.date)?;
+    let content = compile_optional(&selectors.content)?;
+    let headings = compile("h1, h2, h3, h4, h5, h6")?;
+    let links = compile("a[href]")?;
+
+    let doc = Html::parse_document(page);
+    let base = doc
+        .select(&compile("base[href]")?)
+        .next()
+        .and_then(|b| b.value().attr("href"))
+        .map(str::to_string);
+    let items: Vec<Item> = doc
+        .select(&item)
+        .filter_map(|element| {
+            let link_element = match &link {
+                Some(link) => element.select(link).next(),
+                None if element.value().name() == "a" => Some(element),
+                None => element.select(&links).next(),
+            };
+            let href = link_element.and_then(|l| {
+                l.value()
+                    .attr("href")
+                    .or_else(|| l.select(&links).next()?.value().attr("href"))
+            });
+            let title = match &title {
+                Some(title) => element.select(title).next().map(text),
+                None => element.select(&headings).next().map(text),
+            }
+            .filter(|t| !t.is_empty())
+            .or_else(|| link_element.map(text).filter(|t| !t.is_empty()));
+            if title.is_none() && href.is_none() {
+                return None;
+            }
+            Some(Item {
+                title: title.unwrap_or_else(|| "Untitled".to_string()),
+                link: href.map(|h| h.trim().to_string()),
+                published: date
+                    .as_ref()
+                    .and_then(|date| element.select(date).next())
+                    .and_then(|d| {
+                        let value = d.value();
+                        value
+                            .attr("datetime")
+                            .or_else(|| value.attr("content"))
+                            .map(str::to_string)
+                            .or_else(|| Some(text(d)))
+                    })
+                    .filter(|d| !d.is_empty()),
+                content: content
+                    .as_ref()
+                    .and_then(|content| element.select(content).next())
+                    .map(|c| c.inner_html()),
+                // Everything sits on the page, so it resolves against the
+                // page rather than the item's link; an empty base is the
+                // page's own address.
+                base: Some(base.clone().unwrap_or_default()),
+                ..Item::default()
+            })
+        })
+        .collect();
+    if items.is_empty() {
+        return Err(Error::NoItemsMatched(selectors.item.clone()));
+    }
+
+    let page_title = compile("title")?;
+    let mut feed = Feed {
+        format: FeedFormat::Html,
+        title: doc
+            .select(&page_title)
+            .next()
+            .map(text)
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| "Untitled".to_string()),
+        link: None,
+        description: None,
+        icon: None,
+        schedule: Schedule::default(),
+        items,
+        base,
+    };
+    feed::fill_in_items(&mut feed.items);
+    Ok(feed)
+}
+
+/// An element's text with runs of whitespace collapsed.
+fn text(element: ElementRef) -> String {
+    element
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,59 @@
+use lector::feed;
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    std::fs::read(path).unwrap()
+}
+
+#[test]
+fn decodes_iso_8859_1_from_xml_declaration() {
+    let feed = feed::parse_bytes(&fixture("latin1.xml"), Some("application/rss+xml")).unwrap();
+    assert_eq!(feed.title, "Café Müller");
+    assert_eq!(feed.items[0].title, "Crème brûlée à la française");
+    assert_eq!(feed.items[0].content.as_deref(), Some("Señor Niño"));
+}
+
+#[test]
+fn decodes_windows_1252_from_content_type() {
+    let feed = feed::parse_bytes(
+        &fixture("windows1252.xml"),
+        Some("text/xml; charset=\"windows-1252\""),
+    )
+    .unwrap();
+    assert_eq!(feed.title, "“Smart” quotes");
+    assert_eq!(feed.items[0].title, "Price: 20 € — ‘fair’");
+}
+
+#[test]
+fn falls_back_to_windows_1252_for_undeclared_non_utf8() {
+    let feed = feed::parse_bytes(&fixture("windows1252.xml"), None).unwrap();
+    assert_eq!(feed.items[0].content.as_deref(), Some("Ellipsis…"));
+}
+
+#[test]
+fn decodes_shift_jis() {
+    let feed = feed::parse_bytes(&fixture("shift_jis.xml"), Some("text/xml")).unwrap();
+    assert_eq!(feed.title, "日本語のブログ");
+    assert_eq!(feed.items[0].title, "こんにちは世界");
+    assert_eq!(feed.items[0].content.as_deref(), Some("テスト記事です"));
+}
+
+#[test]
+fn content_type_charset_wins_over_xml_declaration() {
+    // The declaration claims UTF-8 but the server knows better.
+    let body = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><rss version=\"2.0\"><channel><title>Caf\u{e9}</title></channel></rss>";
+    let bytes = encoding_rs::WINDOWS_1252.encode(body).0;
+    let feed = feed::parse_bytes(&bytes, Some("text/xml; charset=iso-8859-1")).unwrap();
+    assert_eq!(feed.title, "Café");
+}
+
+#[test]
+fn byte_order_mark_wins_over_everything() {
+    let feed = feed::parse_bytes(
+        &fixture("utf16_bom.xml"),
+        Some("application/xml; charset=iso-8859-1"),
+    )
+    .unwrap();
+    assert_eq!(feed.title, "Ελληνικά");
+    assert_eq!(feed.items[0].title, "Καλημέρα");
+}
@@ -0,0 +1,22 @@
+use lector::db;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::{Pool, Sqlite};
+use tauri::async_runtime::block_on;
+
+/// A fresh in-memory database with every migration applied. One connection
+/// that never closes, since each in-memory connection is its own database.
+pub fn database() -> Pool<Sqlite> {
+    block_on(async {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        for migration in db::migrations() {
+            sqlx::raw_sql(migration.sql).execute(&pool).await.unwrap();
+        }
+        pool
+    })
+}
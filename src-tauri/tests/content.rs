@@ -0,0 +1,28 @@
+use lector::{content, sanitize};
+
+#[test]
+fn resolves_relative_urls_before_storing() {
+    let html = r##"<p><a href="../about">About</a> <a href="#notes">Notes</a></p><img src="/img/a.png" srcset="a-1x.png 1x, /img/a-2x.png 2x">"##;
+    let prepared = content::prepare(html, Some("https://example.com/posts/entry"));
+    assert!(prepared.contains(r#"href="https://example.com/about""#));
+    assert!(prepared.contains(r##"href="#notes""##));
+    // Images are absolute by the time they're pointed at the cache.
+    assert!(prepared.contains("url=https%3A%2F%2Fexample.com%2Fimg%2Fa.png"));
+    assert!(prepared.contains("url=https%3A%2F%2Fexample.com%2Fposts%2Fa-1x.png 1x"));
+    assert!(prepared.contains("url=https%3A%2F%2Fexample.com%2Fimg%2Fa-2x.png 2x"));
+}
+
+#[test]
+fn leaves_relative_urls_without_a_base() {
+    let prepared = content::prepare(r#"<a href="/about">About</a>"#, None);
+    assert!(prepared.contains(r#"href="/about""#));
+}
+
+#[test]
+fn stores_the_sanitizer_output() {
+    let html = r#"<p onclick="x()">Hi</p><picture><source srcset="/a.webp 2x"><img src="/a.png" onerror="x()"></picture><script>x()</script>"#;
+    let prepared = content::prepare(html, Some("https://example.com/"));
+    assert!(prepared.contains("url=https%3A%2F%2Fexample.com%2Fa.png"));
+    assert!(!prepared.contains("onerror"));
+    assert_eq!(sanitize::clean(&prepared), prepared);
+}
@@ -0,0 +1,66 @@
+use lector::date;
+
+fn ts(value: &str) -> Option<i64> {
+    date::timestamp_ms(value)
+}
+
+/// 2025-06-10T04:00:00Z
+const EXPECTED: i64 = 1_749_528_000_000;
+
+#[test]
+fn parses_rfc2822_and_rfc3339() {
+    assert_eq!(ts("Tue, 10 Jun 2025 04:00:00 GMT"), Some(EXPECTED));
+    assert_eq!(ts("Tue, 10 Jun 2025 06:00:00 +0200"), Some(EXPECTED));
+    assert_eq!(ts("2025-06-10T04:00:00Z"), Some(EXPECTED));
+    assert_eq!(ts("2025-06-10T04:00:00.000Z"), Some(EXPECTED));
+    assert_eq!(ts("2025-06-10T09:30:00+05:30"), Some(EXPECTED));
+}
+
+#[test]
+fn parses_iso_variants() {
+    assert_eq!(ts("2025-06-10T04:00Z"), Some(EXPECTED));
+    assert_eq!(ts("2025-06-10 04:00:00"), Some(EXPECTED));
+    assert_eq!(ts("2025-06-10T06:00:00+0200"), Some(EXPECTED));
+    assert_eq!(ts("2025-06-10"), Some(EXPECTED - 4 * 3_600_000));
+}
+
+#[test]
+fn parses_named_time_zones() {
+    assert_eq!(ts("Tue, 10 Jun 2025 00:00:00 EDT"), Some(EXPECTED));
+    assert_eq!(ts("Mon, 09 Jun 2025 23:00:00 EST"), Some(EXPECTED));
+    assert_eq!(ts("Tue, 10 Jun 2025 06:00:00 CEST"), Some(EXPECTED));
+    assert_eq!(ts("Tue, 10 Jun 2025 13:00:00 JST"), Some(EXPECTED));
+}
+
+#[test]
+fn parses_broken_rfc822() {
+    // Two-digit year.
+    assert_eq!(ts("Tue, 10 Jun 25 04:00:00 GMT"), Some(EXPECTED));
+    // Missing seconds.
+    assert_eq!(ts("Tue, 10 Jun 2025 04:00 GMT"), Some(EXPECTED));
+    // Wrong weekday, no weekday, no zone.
+    assert_eq!(ts("Fri, 10 Jun 2025 04:00:00 GMT"), Some(EXPECTED));
+    assert_eq!(ts("10 Jun 2025 04:00:00"), Some(EXPECTED));
+    // Full month name, single-digit hour, colon in the offset.
+    assert_eq!(ts("Tuesday, 10 June 2025 6:00:00 +02:00"), Some(EXPECTED));
+    // Month before day.
+    assert_eq!(ts("June 10, 2025 04:00:00 UTC"), Some(EXPECTED));
+}
+
+#[test]
+fn parses_localized_month_names() {
+    assert_eq!(ts("Di, 10 Juni 2025 06:00:00 +0200"), Some(EXPECTED));
+    assert_eq!(ts("mar., 10 juin 2025 06:00:00 +0200"), Some(EXPECTED));
+    assert_eq!(ts("mar, 10 jun 2025 06:00:00 +0200"), Some(EXPECTED));
+    assert_eq!(ts("10 giugno 2025 04:00:00 GMT"), Some(EXPECTED));
+    assert_eq!(ts("13 Dez 2024 00:00:00 GMT"), Some(1_734_048_000_000));
+}
+
+#[test]
+fn rejects_garbage() {
+    assert_eq!(ts(""), None);
+    assert_eq!(ts("yesterday"), None);
+    assert_eq!(ts("Tue, 31 Feb 2025 04:00:00 GMT"), None);
+    assert_eq!(ts("Thu, 01 Jan 1970 00:00:00 GMT"), None);
+    assert_eq!(ts("Mon, 01 Jan 1900 00:00:00 GMT"), None);
+}
@@ -0,0 +1,339 @@
+mod common;
+
+use common::database;
+use lector::db;
+use lector::feed::{self, Item};
+use sqlx::{Pool, Sqlite};
+use tauri::async_runtime::block_on;
+
+fn item(id: &str, title: &str) -> Item {
+    Item {
+        id: id.to_string(),
+        title: title.to_string(),
+        content: Some(format!("<p>{title}</p>")),
+        ..Item::default()
+    }
+}
+
+async fn subscribe(pool: &Pool<Sqlite>, url: &str, items: &[Item]) {
+    db::insert_feed(pool, url, url).await.unwrap();
+    db::upsert_articles(pool, url, url, items).await.unwrap();
+}
+
+/// Every stored article as (id, feed_url, is_read, is_starred), by id.
+async fn articles(pool: &Pool<Sqlite>) -> Vec<(String, String, bool, bool)> {
+    sqlx::query_as("SELECT id, feed_url, is_read, is_starred FROM articles ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+async fn feeds(pool: &Pool<Sqlite>) -> Vec<String> {
+    sqlx::query_scalar("SELECT url FROM feeds ORDER BY url")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+#[test]
+fn moving_a_feed_rewrites_article_ids() {
+    let pool = database();
+    block_on(async {
+        subscribe(&pool, "http://old.example", &[item("1", "One")]).await;
+        sqlx::query("UPDATE articles SET is_starred = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        db::move_feed(&pool, "http://old.example", "https://new.example")
+            .await
+            .unwrap();
+
+        assert_eq!(feeds(&pool).await, ["https://new.example"]);
+        assert_eq!(
+            articles(&pool).await,
+            [(
+                "https://new.example::1".to_string(),
+                "https://new.example".to_string(),
+                false,
+                true
+            )]
+        );
+    });
+}
+
+#[test]
+fn moving_onto_an_existing_feed_merges_them() {
+    let pool = database();
+    block_on(async {
+        subscribe(
+            &pool,
+            "http://old.example",
+            &[item("1", "One"), item("2", "Two")],
+        )
+        .await;
+        subscribe(&pool, "https://new.example", &[item("1", "One")]).await;
+        sqlx::query("UPDATE articles SET is_read = 1 WHERE feed_url = 'http://old.example'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        db::move_feed(&pool, "http://old.example", "https://new.example")
+            .await
+            .unwrap();
+
+        assert_eq!(feeds(&pool).await, ["https://new.example"]);
+        // The article both feeds had keeps the surviving feed's state; the
+        // other one comes over with its own.
+        assert_eq!(
+            articles(&pool).await,
+            [
+                (
+                    "https://new.example::1".to_string(),
+                    "https://new.example".to_string(),
+                    false,
+                    false
+                ),
+                (
+                    "https://new.example::2".to_string(),
+                    "https://new.example".to_string(),
+                    true,
+                    false
+                ),
+            ]
+        );
+    });
+}
+
+const BLOG: &str = "https://blog.example/feed.xml";
+
+fn blog(items: &str) -> Vec<Item> {
+    let mut feed = feed::parse(&format!(
+        r#"<rss version="2.0"><channel><title>Blog</title><link>https://blog.example/</link>{items}</channel></rss>"#
+    ))
+    .unwrap();
+    feed.resolve_urls(BLOG);
+    feed.items
+}
+
+/// A row as the webview stored it, keyed on link or title and without a
+/// content hash, and flagged for adoption the way the migration does.
+async fn insert_legacy(pool: &Pool<Sqlite>, link: &str, title: &str, read: bool, starred: bool) {
+    let key = if link.is_empty() { title } else { link };
+    sqlx::query(
+        "INSERT INTO articles (id, feed_url, feed_name, title, link, published, published_ts, content, is_read, is_starred, fetched_at)
+         VALUES ($1, $2, 'Blog', $3, $4, 'Mon, 02 Jun 2025 10:00:00 GMT', 0, '<p>Old</p>', $5, $6, 0)",
+    )
+    .bind(format!("{BLOG}::{key}"))
+    .bind(BLOG)
+    .bind(title)
+    .bind(link)
+    .bind(read)
+    .bind(starred)
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query("UPDATE feeds SET legacy_ids = 1")
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[test]
+fn adopts_legacy_article_ids() {
+    let pool = database();
+    block_on(async {
+        db::insert_feed(&pool, BLOG, "Blog").await.unwrap();
+        insert_legacy(&pool, "\n  /posts/1 ", "Relative", true, false).await;
+        insert_legacy(&pool, "", "No link", false, true).await;
+        insert_legacy(&pool, "https://blog.example/old", "Old", true, true).await;
+
+        let items = blog(
+            "<item><title>Relative</title><link>/posts/1</link><guid>p1</guid></item>
+             <item><title>No link</title><guid>p2</guid></item>
+             <item><title>New</title><guid>p3</guid></item>",
+        );
+        let new_items = db::upsert_articles(&pool, BLOG, "Blog", &items)
+            .await
+            .unwrap();
+        assert_eq!(new_items, 1);
+
+        let stored = articles(&pool).await;
+        assert_eq!(stored.len(), 4);
+        let state = |id: &str| {
+            let id = format!("{BLOG}::{id}");
+            stored
+                .iter()
+                .find(|row| row.0 == id)
+                .map(|row| (row.2, row.3))
+        };
+        assert_eq!(state("p1"), Some((true, false)));
+        assert_eq!(state("p2"), Some((false, true)));
+        assert_eq!(state("p3"), Some((false, false)));
+        // Gone from the feed, so it gets the id a guid-less item would.
+        let old = stored
+            .iter()
+            .find(|row| row.0.starts_with(&format!("{BLOG}::hash:")))
+            .unwrap();
+        assert_eq!((old.2, old.3), (true, true));
+
+        // Adoption happens once; later refreshes leave the ids alone.
+        db::upsert_articles(&pool, BLOG, "Blog", &items)
+            .await
+            .unwrap();
+        assert_eq!(articles(&pool).await, stored);
+    });
+}
+
+async fn revision_count(pool: &Pool<Sqlite>) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM article_revisions")
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+async fn updated_at(pool: &Pool<Sqlite>, id: &str) -> Option<i64> {
+    sqlx::query_scalar("SELECT updated_at FROM articles WHERE id = $1")
+        .bind(id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[test]
+fn records_a_revision_when_an_item_changes() {
+    let pool = database();
+    block_on(async {
+        subscribe(&pool, "https://news.example", &[item("1", "First")]).await;
+        db::upsert_articles(&pool, "https://news.example", "News", &[item("1", "First")])
+            .await
+            .unwrap();
+        assert_eq!(revision_count(&pool).await, 0);
+
+        db::upsert_articles(&pool, "https://news.example", "News", &[item("1", "Fixed")])
+            .await
+            .unwrap();
+        let revisions = db::list_revisions(&pool, "https://news.example::1")
+            .await
+            .unwrap();
+        let titles: Vec<&str> = revisions.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, ["First", "Fixed"]);
+        assert!(updated_at(&pool, "https://news.example::1").await.is_some());
+    });
+}
+
+#[test]
+fn takes_the_first_hash_of_a_legacy_row_as_its_baseline() {
+    let pool = database();
+    block_on(async {
+        db::insert_feed(&pool, BLOG, "Blog").await.unwrap();
+        insert_legacy(&pool, "https://blog.example/a", "A", true, false).await;
+
+        // The webview's copy differs from the Rust parse, but that's no edit.
+        let items = blog(
+            "<item><title>A</title><link>https://blog.example/a</link><guid>a</guid><description>New parse</description></item>",
+        );
+        db::upsert_articles(&pool, BLOG, "Blog", &items)
+            .await
+            .unwrap();
+        let id = format!("{BLOG}::a");
+        assert_eq!(revision_count(&pool).await, 0);
+        assert_eq!(updated_at(&pool, &id).await, None);
+
+        // From then on changes count.
+        let edited = blog(
+            "<item><title>A</title><link>https://blog.example/a</link><guid>a</guid><description>Edited</description></item>",
+        );
+        db::upsert_articles(&pool, BLOG, "Blog", &edited)
+            .await
+            .unwrap();
+        assert_eq!(revision_count(&pool).await, 1);
+        assert!(updated_at(&pool, &id).await.is_some());
+    });
+}
+
+#[test]
+fn remembers_file_feeds_the_user_unsubscribed_from() {
+    let pool = database();
+    block_on(async {
+        let url = "file:///feeds/blog.xml";
+        db::insert_feed(&pool, url, "Blog").await.unwrap();
+        sqlx::query("DELETE FROM feeds WHERE url = $1")
+            .bind(url)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let files = db::list_directory_files(&pool).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].url, url);
+        assert!(files[0].unsubscribed_at.is_some());
+
+        // Subscribing again, by hand, lifts it.
+        db::insert_feed(&pool, url, "Blog").await.unwrap();
+        assert!(db::list_directory_files(&pool).await.unwrap().is_empty());
+    });
+}
+
+#[test]
+fn records_unreadable_files_with_their_modification_time() {
+    let pool = database();
+    block_on(async {
+        let url = "file:///feeds/broken.xml";
+        db::record_unreadable_file(&pool, url, Some(1), "bad XML")
+            .await
+            .unwrap();
+        db::record_unreadable_file(&pool, url, Some(2), "still bad")
+            .await
+            .unwrap();
+
+        let files = db::list_directory_files(&pool).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].modified_at, Some(2));
+        assert_eq!(files[0].last_error.as_deref(), Some("still bad"));
+        assert_eq!(files[0].unsubscribed_at, None);
+    });
+}
+
+#[test]
+fn stores_newsletters_with_their_message_ids() {
+    let pool = database();
+    block_on(async {
+        let url = "mailto:news@example.com";
+        let new_items = db::store_newsletters(
+            &pool,
+            url,
+            "News",
+            &[item("1", "Issue 1")],
+            &["<1@example.com>"],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(new_items, 1);
+        assert_eq!(feeds(&pool).await, [url]);
+        assert_eq!(
+            db::consumed_message_ids(&pool).await.unwrap(),
+            ["<1@example.com>".to_string()].into()
+        );
+    });
+}
+
+#[test]
+fn leaves_newsletter_feeds_out_of_the_refresh_schedule() {
+    let pool = database();
+    block_on(async {
+        db::insert_feed(&pool, BLOG, "Blog").await.unwrap();
+        db::store_newsletters(&pool, "mailto:news@example.com", "News", &[], &[])
+            .await
+            .unwrap();
+
+        let scheduled: Vec<String> = db::list_feed_schedules(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|feed| feed.url)
+            .collect();
+        assert_eq!(scheduled, [BLOG]);
+    });
+}
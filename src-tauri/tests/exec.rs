@@ -0,0 +1,73 @@
+use lector::error::Error;
+use lector::exec::{self, Source};
+use tauri::async_runtime::block_on;
+
+#[test]
+fn reads_newsboat_style_sources() {
+    assert_eq!(
+        exec::source("https://example.com/feed.xml"),
+        Source::Http("https://example.com/feed.xml")
+    );
+    assert_eq!(
+        exec::source("file:///var/build/changes.atom"),
+        Source::File("file:///var/build/changes.atom")
+    );
+    assert_eq!(
+        exec::source("exec:~/bin/changelog --since 7d"),
+        Source::Exec("~/bin/changelog --since 7d")
+    );
+    assert_eq!(
+        exec::source("filter:fix-feed.py --mode a:b:https://vendor.example.com/rss?x=1"),
+        Source::Filter {
+            command: "fix-feed.py --mode a:b",
+            url: "https://vendor.example.com/rss?x=1",
+        }
+    );
+    assert_eq!(
+        exec::document_url("filter:cat:http://example.com/feed"),
+        "http://example.com/feed"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_commands_and_filters() {
+    let output = block_on(exec::run("printf '<rss/>'", None)).unwrap();
+    assert_eq!(output, b"<rss/>");
+
+    let filtered = block_on(exec::run("tr a-z A-Z", Some(b"<title>hi</title>"))).unwrap();
+    assert_eq!(filtered, b"<TITLE>HI</TITLE>");
+}
+
+#[cfg(unix)]
+#[test]
+fn failures_carry_stderr() {
+    let err = block_on(exec::run("echo 'no such changelog' >&2; exit 3", None)).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::CommandFailed {
+            status: Some(3),
+            ..
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "command exited with status 3: no such changelog"
+    );
+}
+
+#[test]
+fn command_sources_follow_the_config_file() {
+    let dir = std::env::temp_dir().join(format!("lector-exec-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join(exec::CONFIG_FILE);
+    exec::init(&dir);
+
+    assert!(!block_on(exec::enabled()));
+    std::fs::write(&config, r#"{"commandSources": true}"#).unwrap();
+    assert!(block_on(exec::enabled()));
+    std::fs::write(&config, r#"{"commandSources": false}"#).unwrap();
+    assert!(!block_on(exec::enabled()));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
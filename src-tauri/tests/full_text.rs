@@ -0,0 +1,37 @@
+use lector::full_text;
+use url::Url;
+
+#[test]
+fn extracts_main_content_without_boilerplate() {
+    let html = include_str!("fixtures/article.html");
+    let page = Url::parse("https://shore.example.com/2024/tide-pools").unwrap();
+    let content = full_text::extract(html, &page).unwrap();
+
+    assert!(content.contains("Tide pools are small, rocky basins"));
+    assert!(content.contains("leave everything where you found it"));
+    assert!(content.contains("https://shore.example.com/guides/tide-pools"));
+    assert!(content.contains("lector-img://"));
+    for boilerplate in ["Archive", "newsletter", "Share this", "Copyright"] {
+        assert!(!content.contains(boilerplate), "kept {boilerplate}");
+    }
+}
+
+#[test]
+fn prefers_marked_article_body() {
+    let body = "A paragraph with enough words in it to count as an article. ".repeat(6);
+    let html = format!(
+        r#"<body><div class="content"><p>{body}</p><div itemprop="articleBody"><p>{body}</p><p>Marked.</p></div></div></body>"#
+    );
+    let page = Url::parse("https://example.com/post").unwrap();
+    let content = full_text::extract(&html, &page).unwrap();
+    assert!(content.starts_with("<p>"));
+    assert!(content.ends_with("<p>Marked.</p>"));
+    assert_eq!(content.matches("A paragraph").count(), 6);
+}
+
+#[test]
+fn rejects_pages_without_enough_text() {
+    let html = "<body><div><p>Just a short teaser, nothing more to see here.</p></div></body>";
+    let page = Url::parse("https://example.com/post").unwrap();
+    assert_eq!(full_text::extract(html, &page), None);
+}
@@ -0,0 +1,32 @@
+use lector::icons;
+use url::Url;
+
+#[test]
+fn prefers_icons_over_touch_icons_in_document_order() {
+    let html = r##"<html><head>
+        <link rel="apple-touch-icon" sizes="180x180" href="/apple-touch-icon.png">
+        <link rel="stylesheet" href="/style.css">
+        <link rel="mask-icon" href="/mask.svg" color="#000">
+        <link rel="icon" type="image/png" sizes="32x32" href="/favicon-32.png">
+        <link rel="Shortcut Icon" href="https://cdn.example.com/favicon.ico">
+    </head></html>"##;
+    let page = Url::parse("https://example.com/blog/").unwrap();
+    assert_eq!(
+        icons::page_icons(html, &page),
+        vec![
+            "https://example.com/favicon-32.png",
+            "https://cdn.example.com/favicon.ico",
+            "https://example.com/apple-touch-icon.png",
+        ]
+    );
+}
+
+#[test]
+fn resolves_icons_against_base_href() {
+    let html = r#"<head><base href="https://static.example.com/assets/"><link rel="icon" href="icon.svg"></head>"#;
+    let page = Url::parse("https://example.com/").unwrap();
+    assert_eq!(
+        icons::page_icons(html, &page),
+        vec!["https://static.example.com/assets/icon.svg"]
+    );
+}
@@ -0,0 +1,30 @@
+use lector::image_cache;
+
+#[test]
+fn rewrites_image_sources_through_the_cache() {
+    let html = r#"<p>Intro</p><img src="https://cdn.example.com/a.jpg?w=1&amp;h=2" alt="A"><picture><source srcset="https://cdn.example.com/b.webp 1x, https://cdn.example.com/b@2x.webp 2x"></picture><img src="data:image/gif;base64,R0lGOD"><img src="/relative.png">"#;
+    let rewritten = image_cache::rewrite(html);
+
+    let a = image_cache::proxied("https://cdn.example.com/a.jpg?w=1&h=2");
+    let b = image_cache::proxied("https://cdn.example.com/b.webp");
+    let b2 = image_cache::proxied("https://cdn.example.com/b@2x.webp");
+    assert!(rewritten.starts_with("<p>Intro</p>"));
+    assert!(rewritten.contains(&format!(r#"src="{}""#, a.replace('&', "&amp;"))));
+    assert!(rewritten.contains(&format!(r#"srcset="{b} 1x, {b2} 2x""#)));
+    assert!(rewritten.contains(r#"src="data:image/gif;base64,R0lGOD""#));
+    assert!(rewritten.contains(r#"src="/relative.png""#));
+    assert!(!rewritten.contains(r#""https://cdn"#));
+}
+
+#[test]
+fn leaves_non_http_urls_alone() {
+    assert_eq!(image_cache::proxied("/relative.png"), "/relative.png");
+    assert_eq!(
+        image_cache::proxied("data:image/png;base64,AA"),
+        "data:image/png;base64,AA"
+    );
+    assert_ne!(
+        image_cache::proxied("https://example.com/x.png"),
+        "https://example.com/x.png"
+    );
+}
@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use lector::content;
+use lector::mail::{self, MailboxKind};
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn read(path: &Path, kind: MailboxKind) -> Vec<Vec<u8>> {
+    let mut messages = Vec::new();
+    mail::read_mailbox(path, kind, |raw| messages.push(raw)).unwrap();
+    messages
+}
+
+#[test]
+fn reads_newsletters_from_mbox() {
+    let messages = read(&fixture("newsletters.mbox"), MailboxKind::Mbox);
+    assert_eq!(messages.len(), 3);
+    let newsletters: Vec<_> = messages
+        .iter()
+        .filter_map(|raw| mail::parse_message(raw))
+        .collect();
+    assert_eq!(newsletters.len(), 3);
+
+    let issue = &newsletters[0];
+    assert_eq!(issue.message_id, "issue-12@tidewatch.example");
+    assert_eq!(issue.sender, "news@tidewatch.example");
+    assert_eq!(issue.sender_name.as_deref(), Some("Tidewatch Weekly"));
+    assert_eq!(issue.item.id, "issue-12@tidewatch.example");
+    assert_eq!(issue.item.title, "Issue 12: Kelp forests — back");
+    assert_eq!(issue.item.published_ts, Some(1714640400000));
+    assert_eq!(
+        issue.item.image.as_deref(),
+        Some("https://tidewatch.example/kelp.jpg")
+    );
+    // Ingest runs bodies through the same cleaning as feed content.
+    let stored = content::prepare(issue.item.content.as_deref().unwrap(), None);
+    assert!(stored.contains("Forests regrew"));
+    for unsafe_markup in ["<script", "onclick", "<style"] {
+        assert!(!stored.contains(unsafe_markup), "kept {unsafe_markup}");
+    }
+
+    let digest = &newsletters[1];
+    assert_eq!(digest.sender, "digest@harbor.example");
+    assert_eq!(digest.sender_name, None);
+    assert!(digest.message_id.starts_with("hash:"));
+    assert!(
+        digest
+            .item
+            .content
+            .as_deref()
+            .unwrap()
+            .contains("Moorings reopen on Monday.")
+    );
+
+    assert_eq!(newsletters[2].sender, "news@tidewatch.example");
+}
+
+#[test]
+fn reads_new_and_current_maildir_messages() {
+    let messages = read(&fixture("maildir"), MailboxKind::Maildir);
+    let mut ids: Vec<String> = messages
+        .iter()
+        .filter_map(|raw| mail::parse_message(raw))
+        .map(|n| n.message_id)
+        .collect();
+    ids.sort();
+    assert_eq!(
+        ids,
+        vec!["digest-17@harbor.example", "issue-12@tidewatch.example"]
+    );
+}
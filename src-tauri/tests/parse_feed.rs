@@ -0,0 +1,304 @@
+use lector::feed::{self, FeedFormat};
+
+fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn parses_rss2() {
+    let feed = feed::parse(&fixture("rss2.xml")).unwrap();
+    assert_eq!(feed.format, FeedFormat::Rss2);
+    assert_eq!(feed.title, "Example Blog");
+    assert_eq!(feed.link.as_deref(), Some("https://example.com/"));
+    assert_eq!(feed.schedule.interval_minutes, Some(120));
+    assert_eq!(feed.schedule.skip_hours, vec![0, 0, 3]);
+    assert_eq!(feed.schedule.skip_days, vec!["Saturday", "Sunday"]);
+    assert_eq!(feed.items.len(), 3);
+
+    let first = &feed.items[0];
+    assert_eq!(first.title, "First post");
+    assert_eq!(first.link.as_deref(), Some("https://example.com/first"));
+    assert_eq!(
+        first.published.as_deref(),
+        Some("Tue, 10 Jun 2025 04:00:00 GMT")
+    );
+    assert_eq!(first.content.as_deref(), Some("<p>Full <b>body</b></p>"));
+    assert_eq!(first.author.as_deref(), Some("Jane Doe"));
+
+    let second = &feed.items[1];
+    assert_eq!(second.published.as_deref(), Some("2025-06-11T09:30:00Z"));
+    assert_eq!(
+        second.content.as_deref(),
+        Some("<p>Escaped &amp; encoded</p>")
+    );
+    assert_eq!(
+        second.author.as_deref(),
+        Some("john@example.com (John Roe)")
+    );
+
+    let third = &feed.items[2];
+    assert_eq!(third.title, "Untitled");
+    assert_eq!(third.link, None);
+}
+
+#[test]
+fn parses_rss091_with_doctype() {
+    let feed = feed::parse(&fixture("rss091.xml")).unwrap();
+    assert_eq!(feed.format, FeedFormat::Rss09);
+    assert_eq!(feed.title, "Old School");
+    assert_eq!(feed.items.len(), 1);
+    assert_eq!(feed.items[0].content.as_deref(), Some("Plain description"));
+}
+
+#[test]
+fn parses_rss1_rdf() {
+    let feed = feed::parse(&fixture("rss1.xml")).unwrap();
+    assert_eq!(feed.format, FeedFormat::Rss1);
+    assert_eq!(feed.title, "RDF Site");
+    assert_eq!(feed.schedule.interval_minutes, Some(360));
+    assert_eq!(feed.items.len(), 1);
+
+    let item = &feed.items[0];
+    assert_eq!(item.title, "RDF item");
+    assert_eq!(item.link.as_deref(), Some("https://rdf.example.org/a"));
+    assert_eq!(item.published.as_deref(), Some("2025-05-01T12:00:00+02:00"));
+    assert_eq!(item.author.as_deref(), Some("Ada"));
+}
+
+#[test]
+fn parses_atom() {
+    let feed = feed::parse(&fixture("atom.xml")).unwrap();
+    assert_eq!(feed.format, FeedFormat::Atom);
+    assert_eq!(feed.title, "Atom Site");
+    assert_eq!(feed.link.as_deref(), Some("https://atom.example.net/"));
+    assert_eq!(feed.description.as_deref(), Some("Atom subtitle"));
+    assert_eq!(feed.items.len(), 2);
+
+    let first = &feed.items[0];
+    assert_eq!(first.title, "Escaped &lt;em&gt;title&lt;/em&gt;");
+    assert_eq!(
+        first.link.as_deref(),
+        Some("https://atom.example.net/entries/1")
+    );
+    assert_eq!(first.published.as_deref(), Some("2025-06-10T08:00:00Z"));
+    assert_eq!(
+        first.content.as_deref(),
+        Some("<p>Inline <em>XHTML</em></p>")
+    );
+    assert_eq!(first.author.as_deref(), Some("Entry Author"));
+
+    let second = &feed.items[1];
+    assert_eq!(second.published.as_deref(), Some("2025-06-12T00:00:00Z"));
+    assert_eq!(second.content.as_deref(), Some("<p>HTML summary</p>"));
+    assert_eq!(second.author.as_deref(), Some("Feed Author"));
+}
+
+#[test]
+fn rejects_unknown_root() {
+    assert!(feed::parse("<html><body/></html>").is_err());
+    assert!(feed::parse("not xml").is_err());
+}
+
+#[test]
+fn parses_json_feed() {
+    let feed = feed::parse(&fixture("jsonfeed.json")).unwrap();
+    assert_eq!(feed.format, FeedFormat::JsonFeed);
+    assert_eq!(feed.title, "JSON Blog");
+    assert_eq!(feed.link.as_deref(), Some("https://json.example.com/"));
+    assert_eq!(feed.items.len(), 2);
+
+    let first = &feed.items[0];
+    assert_eq!(first.title, "HTML item");
+    assert_eq!(first.link.as_deref(), Some("https://json.example.com/1"));
+    assert_eq!(
+        first.published.as_deref(),
+        Some("2025-06-01T10:00:00-04:00")
+    );
+    assert_eq!(first.content.as_deref(), Some("<p>Hello <b>world</b></p>"));
+    assert_eq!(first.author.as_deref(), Some("Item Writer"));
+
+    let second = &feed.items[1];
+    assert_eq!(second.title, "Untitled");
+    assert_eq!(
+        second.link.as_deref(),
+        Some("https://elsewhere.example.org/post")
+    );
+    assert_eq!(second.published.as_deref(), Some("2025-06-02T10:00:00Z"));
+    assert_eq!(
+        second.content.as_deref(),
+        Some("<p>Line one &amp; two</p><p>Second &lt;para&gt;</p>")
+    );
+    assert_eq!(second.author.as_deref(), Some("Feed Writer"));
+}
+
+#[test]
+fn sniffs_body_before_content_type() {
+    let body = format!("\n{}", fixture("jsonfeed.json").trim_start());
+    let feed = feed::parse_with_content_type(&body, Some("text/xml")).unwrap();
+    assert_eq!(feed.format, FeedFormat::JsonFeed);
+
+    let feed =
+        feed::parse_with_content_type(&fixture("atom.xml"), Some("application/json")).unwrap();
+    assert_eq!(feed.format, FeedFormat::Atom);
+
+    assert!(feed::parse("{\"title\": \"not a feed\"}").is_err());
+}
+
+#[test]
+fn parses_podcast_enclosures_and_itunes_metadata() {
+    let feed = feed::parse(&fixture("podcast.xml")).unwrap();
+    let episode = &feed.items[0];
+    assert_eq!(episode.enclosures.len(), 1);
+    let audio = &episode.enclosures[0];
+    assert_eq!(audio.url, "https://cdn.example.com/ep12.mp3");
+    assert_eq!(audio.mime_type.as_deref(), Some("audio/mpeg"));
+    assert_eq!(audio.length, Some(48_213_321));
+    assert_eq!(audio.duration, Some(3725));
+    assert_eq!(episode.episode.number, Some(12));
+    assert_eq!(episode.episode.season, Some(2));
+    assert_eq!(
+        episode.episode.image.as_deref(),
+        Some("https://podcast.example.com/ep12.jpg")
+    );
+    assert_eq!(episode.episode.explicit, Some(true));
+
+    // Falls back to the show's artwork and rating; a zero length is unknown.
+    let bonus = &feed.items[1];
+    assert_eq!(bonus.enclosures[0].length, None);
+    assert_eq!(bonus.enclosures[0].duration, Some(754));
+    assert_eq!(
+        bonus.episode.image.as_deref(),
+        Some("https://podcast.example.com/show.jpg")
+    );
+    assert_eq!(bonus.episode.explicit, Some(false));
+}
+
+#[test]
+fn ignores_durations_that_overflow() {
+    for duration in ["1e300:1", "9223372036854775807:59", "-5", "NaN"] {
+        let feed = feed::parse(&format!(
+            r#"<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd"><channel><title>P</title>
+               <item><title>E</title><enclosure url="https://cdn.example.com/e.mp3" type="audio/mpeg"/>
+                 <itunes:duration>{duration}</itunes:duration></item></channel></rss>"#
+        ))
+        .unwrap();
+        assert_eq!(feed.items[0].enclosures[0].duration, None, "{duration}");
+    }
+}
+
+#[test]
+fn parses_atom_and_json_feed_enclosures() {
+    let atom = feed::parse(
+        r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>A</title>
+           <entry><title>E</title><id>e</id>
+             <link rel="enclosure" href="https://cdn.example.com/e.ogg" type="audio/ogg" length="1234"/>
+           </entry></feed>"#,
+    )
+    .unwrap();
+    let entry = &atom.items[0];
+    assert_eq!(entry.link, None);
+    assert_eq!(entry.enclosures[0].url, "https://cdn.example.com/e.ogg");
+    assert_eq!(entry.enclosures[0].length, Some(1234));
+
+    let json = feed::parse(
+        r#"{"version": "https://jsonfeed.org/version/1.1", "title": "J", "items": [
+             {"id": "1", "attachments": [{"url": "https://cdn.example.com/1.mp3",
+               "mime_type": "audio/mpeg", "size_in_bytes": 99, "duration_in_seconds": 61}]}]}"#,
+    )
+    .unwrap();
+    let attachment = &json.items[0].enclosures[0];
+    assert_eq!(attachment.mime_type.as_deref(), Some("audio/mpeg"));
+    assert_eq!(attachment.length, Some(99));
+    assert_eq!(attachment.duration, Some(61));
+}
+
+#[test]
+fn extracts_lead_images() {
+    let feed = feed::parse(&fixture("media.xml")).unwrap();
+    assert_eq!(
+        feed.icon.as_deref(),
+        Some("https://news.example.com/logo.png")
+    );
+    let images: Vec<Option<&str>> = feed.items.iter().map(|i| i.image.as_deref()).collect();
+    assert_eq!(
+        images,
+        vec![
+            Some("https://cdn.example.com/thumb.jpg"),
+            Some("https://cdn.example.com/lead.png"),
+            Some("https://cdn.example.com/body.webp"),
+            None,
+        ]
+    );
+}
+
+#[test]
+fn reads_feed_icons() {
+    let atom = feed::parse(
+        r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>T</title><logo>/logo.png</logo><icon>/favicon.png</icon></feed>"#,
+    )
+    .unwrap();
+    assert_eq!(atom.icon.as_deref(), Some("/favicon.png"));
+
+    let json = feed::parse(
+        r#"{"version": "https://jsonfeed.org/version/1.1", "title": "T", "icon": "https://example.com/big.png", "favicon": "https://example.com/small.png", "items": []}"#,
+    )
+    .unwrap();
+    assert_eq!(json.icon.as_deref(), Some("https://example.com/small.png"));
+
+    let podcast = feed::parse(&fixture("podcast.xml")).unwrap();
+    assert!(podcast.icon.is_some());
+}
+
+#[test]
+fn resolves_relative_links_against_xml_base() {
+    let mut feed = feed::parse(&fixture("relative.xml")).unwrap();
+    let id_before = feed.items[0].id.clone();
+    feed.resolve_urls("https://feeds.example.net/blog.atom");
+
+    assert_eq!(feed.link.as_deref(), Some("https://blog.example.com/"));
+    assert_eq!(
+        feed.icon.as_deref(),
+        Some("https://blog.example.com/favicon.png")
+    );
+
+    let based = &feed.items[0];
+    assert_eq!(based.id, id_before);
+    assert_eq!(
+        based.link.as_deref(),
+        Some("https://cdn.example.com/posts/based")
+    );
+    assert_eq!(based.base.as_deref(), Some("https://cdn.example.com/2025/"));
+    assert_eq!(
+        based.image.as_deref(),
+        Some("https://cdn.example.com/2025/cover.jpg")
+    );
+    assert_eq!(
+        based.enclosures[0].url,
+        "https://cdn.example.com/2025/media/based.mp3"
+    );
+
+    let nested = &feed.items[1];
+    assert_eq!(
+        nested.link.as_deref(),
+        Some("https://blog.example.com/archive/nested")
+    );
+    assert_eq!(
+        nested.base.as_deref(),
+        Some("https://blog.example.com/archive/")
+    );
+}
+
+#[test]
+fn falls_back_to_item_link_and_feed_url() {
+    let mut feed = feed::parse(
+        r#"<rss version="2.0"><channel><title>T</title>
+        <item><title>A</title><link>/posts/a</link><description>&lt;a href="b"&gt;b&lt;/a&gt;</description></item>
+        </channel></rss>"#,
+    )
+    .unwrap();
+    feed.resolve_urls("https://example.com/feed.xml");
+    let item = &feed.items[0];
+    assert_eq!(item.link.as_deref(), Some("https://example.com/posts/a"));
+    assert_eq!(item.base.as_deref(), Some("https://example.com/posts/a"));
+}
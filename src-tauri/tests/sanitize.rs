@@ -0,0 +1,39 @@
+use lector::sanitize;
+
+#[test]
+fn strips_scripts_handlers_and_javascript_urls() {
+    let html = r#"<p onclick="steal()">Hi <a href="javascript:alert(1)">there</a></p><script>alert(1)</script><style>p{}</style><img src="https://example.com/a.png" onerror="steal()"><form action="/x"><input name="q"></form>"#;
+    let clean = sanitize::clean(html);
+    assert!(!clean.contains("onclick"));
+    assert!(!clean.contains("onerror"));
+    assert!(!clean.contains("javascript:"));
+    assert!(!clean.contains("script"));
+    assert!(!clean.contains("<style"));
+    assert!(!clean.contains("<form"));
+    assert!(!clean.contains("<input"));
+    assert!(clean.contains("<p>Hi <a"));
+    assert!(clean.contains(r#"<img src="https://example.com/a.png">"#));
+}
+
+#[test]
+fn keeps_known_embeds_and_drops_other_iframes() {
+    let html = r#"<iframe src="https://www.youtube-nocookie.com/embed/abc" width="560" height="315" allowfullscreen></iframe><iframe src="https://evil.example.com/frame"></iframe><iframe srcdoc="<script>x</script>"></iframe>"#;
+    let clean = sanitize::clean(html);
+    assert!(clean.contains(r#"src="https://www.youtube-nocookie.com/embed/abc""#));
+    assert!(!clean.contains("evil.example.com"));
+    assert!(!clean.contains("srcdoc"));
+    assert_eq!(clean.matches("<iframe").count(), 1);
+}
+
+#[test]
+fn keeps_media_and_cached_images() {
+    let html = r#"<figure><picture><source srcset="lector-img://localhost/?url=https%3A%2F%2Fexample.com%2Fb.webp 2x"><img src="lector-img://localhost/?url=https%3A%2F%2Fexample.com%2Fb.jpg" alt="B"></picture><figcaption>Caption</figcaption></figure><audio controls src="https://example.com/a.mp3"></audio>"#;
+    let clean = sanitize::clean(html);
+    assert!(
+        clean.contains(r#"src="lector-img://localhost/?url=https%3A%2F%2Fexample.com%2Fb.jpg""#)
+    );
+    assert!(clean.contains(r#"alt="B""#));
+    assert!(clean.contains("<source srcset="));
+    assert!(clean.contains("<figcaption>Caption</figcaption>"));
+    assert!(clean.contains(r#"<audio controls="" src="https://example.com/a.mp3">"#));
+}
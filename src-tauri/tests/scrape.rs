@@ -0,0 +1,79 @@
+use lector::error::Error;
+use lector::feed::FeedFormat;
+use lector::scrape::{self, Selectors};
+
+fn selectors() -> Selectors {
+    Selectors {
+        item: "li.notice".into(),
+        date: Some("time, .date".into()),
+        content: Some(".summary".into()),
+        ..Selectors::default()
+    }
+}
+
+#[test]
+fn builds_items_from_selectors() {
+    let mut feed = scrape::parse(include_str!("fixtures/listing.html"), &selectors()).unwrap();
+    feed.resolve_urls("https://harbor.example.gov/notices");
+
+    assert_eq!(feed.format, FeedFormat::Html);
+    assert_eq!(feed.title, "Harbor Council — Notices");
+    assert_eq!(feed.items.len(), 2);
+
+    let first = &feed.items[0];
+    assert_eq!(first.title, "Dredging in the north channel");
+    assert_eq!(
+        first.link.as_deref(),
+        Some("https://harbor.example.gov/notices/2024/dredging.html")
+    );
+    assert_eq!(first.published_ts, Some(1714640400000));
+    assert!(first.content.as_deref().unwrap().contains("<b>Monday</b>"));
+    assert_eq!(
+        first.image.as_deref(),
+        Some("https://harbor.example.gov/notices/img/dredger.jpg")
+    );
+    assert!(first.id.starts_with("hash:"));
+
+    let second = &feed.items[1];
+    assert_eq!(second.title, "Mooring fees");
+    assert_eq!(second.published_ts, Some(1714046400000));
+    assert_eq!(second.content, None);
+}
+
+#[test]
+fn uses_explicit_title_and_link_selectors() {
+    let html = r#"<div class="card"><span class="name">Launch day</span><a class="more" href="/launch">Read more</a><a href="/other">Other</a></div>"#;
+    let feed = scrape::parse(
+        html,
+        &Selectors {
+            item: ".card".into(),
+            title: Some(".name".into()),
+            link: Some("a.more".into()),
+            ..Selectors::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(feed.items[0].title, "Launch day");
+    assert_eq!(feed.items[0].link.as_deref(), Some("/launch"));
+}
+
+#[test]
+fn reports_bad_selectors_and_empty_matches() {
+    let html = include_str!("fixtures/listing.html");
+    let invalid = Selectors {
+        item: "li[".into(),
+        ..Selectors::default()
+    };
+    assert!(matches!(
+        scrape::parse(html, &invalid),
+        Err(Error::InvalidSelector(_))
+    ));
+    let unmatched = Selectors {
+        item: "article".into(),
+        ..Selectors::default()
+    };
+    assert!(matches!(
+        scrape::parse(html, &unmatched),
+        Err(Error::NoItemsMatched(_))
+    ));
+}
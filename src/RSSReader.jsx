@@ -1,52 +1,18 @@
 import { useState, useEffect, useCallback, useRef } from "react";
+import { invoke, convertFileSrc } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { open } from "@tauri-apps/plugin-shell";
-import { fetch as tauriFetch } from "@tauri-apps/plugin-http";
-import { initDb, listFeeds, addFeed as dbAddFeed, removeFeed as dbRemoveFeed, listArticles, upsertArticles, markRead as dbMarkRead, toggleRead as dbToggleRead, toggleStar as dbToggleStar, markAllRead as dbMarkAllRead, importFromLocalStorageIfNeeded } from "./db";
-
-function parseRSS(xmlText) {
-  const parser = new DOMParser();
-  const doc = parser.parseFromString(xmlText, "text/xml");
-  const isAtom = !!doc.querySelector("feed");
-  const items = [];
-  if (isAtom) {
-    const feedTitle = doc.querySelector("feed > title")?.textContent || "Untitled";
-    doc.querySelectorAll("entry").forEach((entry) => {
-      items.push({
-        title: entry.querySelector("title")?.textContent || "Untitled",
-        link: entry.querySelector("link[rel='alternate']")?.getAttribute("href") || entry.querySelector("link")?.getAttribute("href") || "",
-        published: entry.querySelector("published")?.textContent || entry.querySelector("updated")?.textContent || "",
-        content: entry.querySelector("content")?.textContent || entry.querySelector("summary")?.textContent || "",
-        author: entry.querySelector("author > name")?.textContent || "",
-      });
-    });
-    return { feedTitle, items };
-  }
-  const channel = doc.querySelector("channel");
-  const feedTitle = channel?.querySelector("title")?.textContent || "Untitled";
-  doc.querySelectorAll("item").forEach((item) => {
-    items.push({
-      title: item.querySelector("title")?.textContent || "Untitled",
-      link: item.querySelector("link")?.textContent || "",
-      published: item.querySelector("pubDate")?.textContent || item.querySelector("dc\\:date")?.textContent || "",
-      content: item.getElementsByTagName("content:encoded")[0]?.textContent || item.querySelector("description")?.textContent || "",
-      author: item.getElementsByTagName("dc:creator")[0]?.textContent || item.querySelector("author")?.textContent || "",
-    });
-  });
-  return { feedTitle, items };
+import { initDb, listFeeds, removeFeed as dbRemoveFeed, listArticles, markRead as dbMarkRead, toggleRead as dbToggleRead, toggleStar as dbToggleStar, markAllRead as dbMarkAllRead, importFromLocalStorageIfNeeded } from "./db";
+
+function formatDuration(seconds) {
+  const h = Math.floor(seconds / 3600), m = Math.floor((seconds % 3600) / 60);
+  return h > 0 ? `${h}h ${m}m` : `${m} min`;
 }
 
-async function fetchFeed(url) {
-  const resp = await tauriFetch(url, {
-    method: "GET",
-    headers: {
-      "Accept": "application/rss+xml, application/atom+xml, application/xml, text/xml, */*",
-      "User-Agent": "Lector/1.0",
-    },
-    connectTimeout: 12000,
-    maxRedirections: 10,
-  });
-  if (!resp.ok) throw new Error(`HTTP ${resp.status}`);
-  return parseRSS(await resp.text());
+function formatBytes(bytes) {
+  if (bytes >= 1024 * 1024 * 1024) return `${(bytes / (1024 * 1024 * 1024)).toFixed(1)} GB`;
+  if (bytes >= 1024 * 1024) return `${Math.round(bytes / (1024 * 1024))} MB`;
+  return `${Math.round(bytes / 1024)} KB`;
 }
 
 function formatDate(dateStr) {
@@ -98,9 +64,17 @@ export default function RSSReader() {
   const [refreshing, setRefreshing] = useState(false);
   const [error, setError] = useState("");
   const [showAddFeed, setShowAddFeed] = useState(false);
+  const [candidates, setCandidates] = useState([]);
+  const [selectors, setSelectors] = useState(null);
+  const [preview, setPreview] = useState(null);
   const [sidebarOpen, setSidebarOpen] = useState(!isMobile);
   const [viewFilter, setViewFilter] = useState("all");
   const [hydrated, setHydrated] = useState(false);
+  const [feedHealth, setFeedHealth] = useState({});
+  const [changes, setChanges] = useState(null);
+  const [episode, setEpisode] = useState(null);
+  const [downloads, setDownloads] = useState({});
+  const [feedIcons, setFeedIcons] = useState({});
   const readerRef = useRef(null);
   const loadSeq = useRef(0);
 
@@ -111,6 +85,22 @@ export default function RSSReader() {
     setArticles(arts);
   }, []);
 
+  // Refreshes can move a feed to a new URL after a permanent redirect, so reload the list too.
+  const reloadFeedHealth = useCallback(async () => {
+    setFeeds(await listFeeds());
+    const unhealthy = await invoke("list_unhealthy_feeds").catch(() => []);
+    setFeedHealth(Object.fromEntries(unhealthy.map((h) => [h.url, h])));
+  }, []);
+
+  const reloadFeedIcons = useCallback(async () => {
+    setFeedIcons(await invoke("list_feed_icons").catch(() => ({})));
+  }, []);
+
+  const reloadDownloads = useCallback(async () => {
+    const rows = await invoke("list_downloads").catch(() => []);
+    setDownloads(Object.fromEntries(rows.map((d) => [d.enclosureId, d])));
+  }, []);
+
   // Hydrate from DB on mount
   useEffect(() => {
     let cancelled = false;
@@ -128,40 +118,146 @@ export default function RSSReader() {
     return () => { cancelled = true; };
   }, []);
 
-  const refreshAllFeeds = useCallback(async () => {
+  const refreshAllFeeds = useCallback(async ({ dueOnly = false } = {}) => {
     if (feeds.length === 0) return;
     setRefreshing(true);
-    const results = await Promise.allSettled(feeds.map(async (feed) => ({ feed, result: await fetchFeed(feed.url) })));
-    for (const r of results) {
-      if (r.status === "fulfilled") {
-        const { feed, result } = r.value;
-        await upsertArticles(feed.url, feed.name || result.feedTitle, result.items);
-      }
-    }
-    await reloadArticles();
+    // The Rust refresh engine sends conditional GETs with per-host limits and upserts directly.
+    await invoke(dueOnly ? "refresh_due_feeds" : "refresh_all_feeds").catch((e) => console.error("refresh error:", e));
+    await Promise.all([reloadArticles(), reloadFeedHealth()]);
     setRefreshing(false);
-  }, [feeds, reloadArticles]);
+  }, [feeds, reloadArticles, reloadFeedHealth]);
 
   // Auto-refresh on hydration
   useEffect(() => {
-    if (hydrated && feeds.length > 0) refreshAllFeeds();
+    if (hydrated && feeds.length > 0) refreshAllFeeds({ dueOnly: true });
   }, [hydrated]);
 
-  const addFeed = async () => {
-    if (!newFeedUrl.trim()) return;
-    let url = newFeedUrl.trim();
-    if (!url.startsWith("http")) url = "https://" + url;
+  // The Rust scheduler refreshes in the background and tells us when new articles land
+  useEffect(() => {
+    if (!hydrated) return;
+    reloadFeedHealth();
+    const unlisten = listen("articles-updated", () => { reloadArticles(); reloadFeedHealth(); });
+    return () => { unlisten.then((fn) => fn()); };
+  }, [hydrated, reloadArticles, reloadFeedHealth]);
+
+  // Icons are looked up in the background after refreshes and new subscriptions
+  useEffect(() => {
+    if (!hydrated) return;
+    reloadFeedIcons();
+    const unlisten = listen("feed-icons-updated", reloadFeedIcons);
+    return () => { unlisten.then((fn) => fn()); };
+  }, [hydrated, reloadFeedIcons]);
+
+  // Full text is extracted in the background; keep the open article in step with what was stored
+  useEffect(() => {
+    if (!hydrated) return;
+    const unlisten = listen("full-text-updated", reloadArticles);
+    return () => { unlisten.then((fn) => fn()); };
+  }, [hydrated, reloadArticles]);
+
+  useEffect(() => {
+    setSelectedArticle((prev) => {
+      const current = prev && articles.find((a) => a.id === prev.id);
+      if (!current || (current.fullContent === prev.fullContent && current.fullContentError === prev.fullContentError)) return prev;
+      return { ...prev, fullContent: current.fullContent, fullContentError: current.fullContentError };
+    });
+  }, [articles]);
+
+  // Progress events carry the byte counts; finished and failed downloads reload to pick up the file path or error
+  useEffect(() => {
+    if (!hydrated) return;
+    reloadDownloads();
+    const unlisten = listen("download-progress", ({ payload }) => {
+      if (payload.status === "done" || payload.status === "failed") { reloadDownloads(); return; }
+      setDownloads((prev) => ({ ...prev, [payload.enclosureId]: { ...prev[payload.enclosureId], enclosureId: payload.enclosureId, status: payload.status, bytesDownloaded: payload.downloaded, totalBytes: payload.total } }));
+    });
+    return () => { unlisten.then((fn) => fn()); };
+  }, [hydrated, reloadDownloads]);
+
+  useEffect(() => {
+    setChanges(null); setEpisode(null);
+    if (!selectedArticle) return;
+    let cancelled = false;
+    invoke("list_episodes", { feedUrl: null, articleId: selectedArticle.id })
+      .then((found) => { if (!cancelled) setEpisode(found[0] || null); })
+      .catch((e) => console.error("episode error:", e));
+    return () => { cancelled = true; };
+  }, [selectedArticle?.id]);
+
+  const downloadEnclosure = (id) => invoke("download_enclosure", { enclosureId: id }).catch((e) => console.error("download error:", e));
+  const deleteDownload = async (id) => {
+    await invoke("delete_download", { enclosureId: id }).catch((e) => console.error("download error:", e));
+    await reloadDownloads();
+  };
+  const setAutoDownload = async (url, keep) => {
+    await invoke("set_auto_download", { feedUrl: url, keep }).catch((e) => console.error("auto-download error:", e));
+    setFeeds(await listFeeds());
+  };
+  const setFullText = async (url, enabled) => {
+    await invoke("set_full_text", { feedUrl: url, enabled }).catch((e) => console.error("full text error:", e));
+    setFeeds(await listFeeds());
+  };
+  const retryFullText = async (id) => {
+    const fullContent = await invoke("extract_article", { articleId: id }).catch((e) => console.error("full text error:", e));
+    await reloadArticles();
+    if (fullContent) setSelectedArticle((prev) => (prev?.id === id ? { ...prev, fullContent, fullContentError: null } : prev));
+  };
+
+  // Diffs the version the feed replaced most recently against the current one
+  const showChanges = async () => {
+    const revisions = await invoke("get_article_revisions", { articleId: selectedArticle.id });
+    if (revisions.length < 2) return;
+    setChanges(await invoke("diff_article_revisions", { articleId: selectedArticle.id, from: revisions[revisions.length - 2].id, to: null }));
+  };
+
+  const addFeed = async (pickedUrl) => {
+    let url = pickedUrl || newFeedUrl.trim();
+    if (!url) return;
+    // Local files and exec:/filter: commands are read by the Rust core as-is, so there's nothing to discover
+    const command = /^(exec|filter|file):/i.test(url);
+    if (!command && !url.startsWith("http")) url = "https://" + url;
+    if (feeds.some((f) => f.url === url)) { setError("Already subscribed."); return; }
+    setLoading(true); setError("");
+    try {
+      if (!pickedUrl && !command) {
+        // A pasted homepage may advertise several feeds; let the user pick one
+        const found = await invoke("discover_feeds", { url });
+        if (found.length === 0) throw new Error("no feeds found");
+        if (found.length > 1) { setCandidates(found); setLoading(false); return; }
+        url = found[0].url;
+        if (feeds.some((f) => f.url === url)) { setError("Already subscribed."); setLoading(false); return; }
+      }
+      // The Rust core fetches, parses and stores the feed and its articles
+      await invoke("add_feed", { url, name: null });
+      setFeeds(await listFeeds());
+      await reloadArticles();
+      setNewFeedUrl(""); setShowAddFeed(false); setCandidates([]);
+    } catch (e) { console.error("addFeed error:", e); setError(command ? String(e) : "Could not find a feed. Check the URL and try again."); }
+    setLoading(false);
+  };
+
+  // Pages without a feed are scraped with CSS selectors; empty fields fall back to the Rust defaults
+  const scraperSelectors = () => Object.fromEntries(Object.entries(selectors).map(([k, v]) => [k, v.trim() || null]));
+  const scraperUrl = () => { const url = newFeedUrl.trim(); return url.startsWith("http") ? url : "https://" + url; };
+
+  const previewScraper = async () => {
+    setLoading(true); setError(""); setPreview(null);
+    try {
+      setPreview(await invoke("preview_scraper_feed", { url: scraperUrl(), selectors: scraperSelectors() }));
+    } catch (e) { setError(String(e)); }
+    setLoading(false);
+  };
+
+  const addScraperFeed = async () => {
+    const url = scraperUrl();
     if (feeds.some((f) => f.url === url)) { setError("Already subscribed."); return; }
     setLoading(true); setError("");
     try {
-      const result = await fetchFeed(url);
-      const nf = { url, name: result.feedTitle, addedAt: new Date().toISOString() };
-      await dbAddFeed(nf);
-      await upsertArticles(url, result.feedTitle, result.items);
+      await invoke("add_scraper_feed", { url, name: null, selectors: scraperSelectors() });
       setFeeds(await listFeeds());
       await reloadArticles();
-      setNewFeedUrl(""); setShowAddFeed(false);
-    } catch (e) { console.error("addFeed error:", e); setError("Could not fetch feed. Check the URL and try again."); }
+      setNewFeedUrl(""); setShowAddFeed(false); setSelectors(null); setPreview(null);
+    } catch (e) { console.error("addScraperFeed error:", e); setError(String(e)); }
     setLoading(false);
   };
 
@@ -177,9 +273,7 @@ export default function RSSReader() {
     if (feeds.some((f) => f.url === sample.url)) return;
     setLoading(true); setError("");
     try {
-      const result = await fetchFeed(sample.url);
-      await dbAddFeed({ url: sample.url, name: sample.name || result.feedTitle, addedAt: new Date().toISOString() });
-      await upsertArticles(sample.url, sample.name || result.feedTitle, result.items);
+      await invoke("add_feed", { url: sample.url, name: sample.name || null });
       setFeeds(await listFeeds());
       await reloadArticles();
     } catch { setError(`Could not fetch ${sample.name}.`); }
@@ -293,14 +387,43 @@ export default function RSSReader() {
 
           {showAddFeed && (
             <div style={{ padding: "6px 4px 12px" }}>
-              <input type="url" value={newFeedUrl} onChange={(e) => { setNewFeedUrl(e.target.value); setError(""); }} onKeyDown={(e) => e.key === "Enter" && addFeed()} placeholder="Paste RSS feed URL…" className="feed-input" autoFocus />
+              <input type="url" value={newFeedUrl} onChange={(e) => { setNewFeedUrl(e.target.value); setError(""); setCandidates([]); setPreview(null); }} onKeyDown={(e) => e.key === "Enter" && !selectors && addFeed()} placeholder={selectors ? "Page URL…" : "Paste a feed or site URL…"} className="feed-input" autoFocus />
+              {selectors && (
+                <div style={{ display: "flex", flexDirection: "column", gap: 6, marginTop: 8 }}>
+                  {[["item", "Item selector, e.g. article.post"], ["title", "Title (default: first heading)"], ["link", "Link (default: first link)"], ["date", "Date (optional)"], ["content", "Content (optional)"]].map(([key, placeholder]) => (
+                    <input key={key} value={selectors[key]} onChange={(e) => { setSelectors({ ...selectors, [key]: e.target.value }); setPreview(null); }} placeholder={placeholder} className="feed-input" style={{ padding: "7px 10px", fontSize: 12, fontFamily: "monospace" }} />
+                  ))}
+                </div>
+              )}
               <div style={{ display: "flex", gap: 8, marginTop: 8 }}>
-                <button onClick={addFeed} disabled={loading || !newFeedUrl.trim()} className="primary-btn" style={{ opacity: loading || !newFeedUrl.trim() ? 0.5 : 1 }}>
+                {selectors && <button onClick={previewScraper} disabled={loading || !newFeedUrl.trim() || !selectors.item.trim()} className="ghost-btn">Preview</button>}
+                <button onClick={() => (selectors ? addScraperFeed() : addFeed())} disabled={loading || !newFeedUrl.trim() || (selectors && !selectors.item.trim())} className="primary-btn" style={{ opacity: loading || !newFeedUrl.trim() ? 0.5 : 1 }}>
                   {loading ? "Adding…" : "Subscribe"}
                 </button>
-                <button onClick={() => { setShowAddFeed(false); setError(""); }} className="ghost-btn">Cancel</button>
+                <button onClick={() => { setShowAddFeed(false); setError(""); setCandidates([]); setSelectors(null); setPreview(null); }} className="ghost-btn">Cancel</button>
               </div>
+              <button onClick={() => { setSelectors(selectors ? null : { item: "", title: "", link: "", date: "", content: "" }); setError(""); setPreview(null); }} className="ghost-btn" style={{ padding: "6px 0", fontSize: 12 }}>
+                {selectors ? "Subscribe to a feed instead" : "No feed? Scrape the page with CSS selectors"}
+              </button>
+              {preview && (
+                <div style={{ marginTop: 8, display: "flex", flexDirection: "column", gap: 4, fontSize: 12, color: "#5a5040" }}>
+                  <span style={{ fontSize: 11, color: "#8a7e6e" }}>{preview.items.length} items on “{preview.title}”:</span>
+                  {preview.items.slice(0, 8).map((item) => (
+                    <span key={item.id} title={item.link || ""} style={{ whiteSpace: "nowrap", overflow: "hidden", textOverflow: "ellipsis" }}>{item.title}{item.published && <span style={{ color: "#b0a690" }}> · {item.published}</span>}</span>
+                  ))}
+                </div>
+              )}
               {error && <div style={{ color: "#b54a30", fontSize: 12, marginTop: 6 }}>{error}</div>}
+              {candidates.length > 0 && (
+                <div style={{ marginTop: 12, display: "flex", flexDirection: "column", gap: 6 }}>
+                  <span style={{ fontSize: 11, color: "#8a7e6e", marginBottom: 2 }}>This site has {candidates.length} feeds:</span>
+                  {candidates.map((c) => (
+                    <button key={c.url} onClick={() => addFeed(c.url)} disabled={loading} className="sample-btn" title={c.url} style={{ textAlign: "left" }}>
+                      {c.title}
+                    </button>
+                  ))}
+                </div>
+              )}
               {feeds.length === 0 && (
                 <div style={{ marginTop: 12, display: "flex", flexWrap: "wrap", gap: 6 }}>
                   <span style={{ fontSize: 11, color: "#8a7e6e", width: "100%", marginBottom: 2 }}>Quick add:</span>
@@ -318,8 +441,11 @@ export default function RSSReader() {
             {feeds.map((feed) => (
               <div key={feed.url} className="feed-item" style={{ display: "flex", alignItems: "center", borderRadius: 8, background: selectedFeed === feed.url ? "#e8e0d4" : "transparent" }}>
                 <button onClick={() => selectNav(selectedFeed === feed.url ? null : feed.url, "all")} style={{ flex: 1, display: "flex", alignItems: "center", gap: 8, padding: "10px 12px", border: "none", background: "none", cursor: "pointer", fontSize: 14, fontFamily: "inherit", color: "#2a2520", textAlign: "left", borderRadius: 8, overflow: "hidden", minWidth: 0 }}>
-                  <span style={{ fontSize: 8, color: "#8b5e3c", flexShrink: 0 }}>●</span>
+                  {feedIcons[feed.url]
+                    ? <img src={feedIcons[feed.url]} alt="" className="feed-icon" />
+                    : <span style={{ fontSize: 8, color: "#8b5e3c", flexShrink: 0 }}>●</span>}
                   <span style={{ overflow: "hidden", textOverflow: "ellipsis", whiteSpace: "nowrap", flex: 1 }}>{feed.name}</span>
+                  {feedHealth[feed.url] && <span title={feedHealth[feed.url].retiredAt ? "This feed is gone and is no longer refreshed" : `${feedHealth[feed.url].lastError} (${feedHealth[feed.url].consecutiveFailures} failed ${feedHealth[feed.url].consecutiveFailures === 1 ? "refresh" : "refreshes"})`} style={{ fontSize: 12, color: "#b04a3a", flexShrink: 0 }}>⚠</span>}
                   {unreadCount(feed.url) > 0 && <span className="badge">{unreadCount(feed.url)}</span>}
                 </button>
                 <button onClick={(e) => { e.stopPropagation(); removeFeed(feed.url); }} className="remove-btn" title="Unsubscribe">×</button>
@@ -347,8 +473,20 @@ export default function RSSReader() {
             </h2>
           </div>
           <div style={{ display: "flex", gap: 6, flexShrink: 0 }}>
+            {!selectedArticle && selectedFeed && !isMobile && (
+              <select value={feeds.find((f) => f.url === selectedFeed)?.autoDownloadKeep ?? ""} onChange={(e) => setAutoDownload(selectedFeed, e.target.value ? Number(e.target.value) : null)} className="topbar-btn" title="Keep the newest episodes of this feed downloaded">
+                <option value="">Auto-download off</option>
+                {[1, 3, 5, 10].map((n) => <option key={n} value={n}>Keep latest {n}</option>)}
+              </select>
+            )}
+            {!selectedArticle && selectedFeed && !isMobile && (
+              <label className="topbar-btn" title="Fetch each article's page and show its full text" style={{ display: "flex", alignItems: "center", gap: 6 }}>
+                <input type="checkbox" checked={!!feeds.find((f) => f.url === selectedFeed)?.fullText} onChange={(e) => setFullText(selectedFeed, e.target.checked)} />
+                Full text
+              </label>
+            )}
             {!selectedArticle && filteredArticles.length > 0 && !isMobile && <button onClick={handleMarkAllRead} className="topbar-btn">Mark all read</button>}
-            <button onClick={refreshAllFeeds} disabled={refreshing} className="topbar-btn" style={{ opacity: refreshing ? 0.5 : 1 }}>{refreshing ? "…" : "↻"}</button>
+            <button onClick={() => refreshAllFeeds()} disabled={refreshing} className="topbar-btn" style={{ opacity: refreshing ? 0.5 : 1 }}>{refreshing ? "…" : "↻"}</button>
           </div>
         </div>
 
@@ -359,6 +497,7 @@ export default function RSSReader() {
                 <span className="feed-tag">{selectedArticle.feedName}</span>
                 {selectedArticle.author && <span style={{ fontSize: 13, color: "#5a5040" }}>{selectedArticle.author}</span>}
                 <span style={{ fontSize: 13, color: "#b0a690" }}>{formatDate(selectedArticle.published)}</span>
+                {selectedArticle.updatedAt && <span className="updated-tag">Updated</span>}
               </div>
               <h1 style={{ fontFamily: "'Newsreader', Georgia, serif", fontSize: isMobile ? 24 : 32, fontWeight: 500, lineHeight: 1.25, color: "#1a1510", marginBottom: 14, letterSpacing: "-0.015em" }}>{selectedArticle.title}</h1>
               <div style={{ display: "flex", alignItems: "center", gap: 14, marginBottom: 24, paddingBottom: 18, borderBottom: "1px solid #e8e0d4", flexWrap: "wrap" }}>
@@ -366,8 +505,49 @@ export default function RSSReader() {
                   {selectedArticle.is_starred ? "★ Starred" : "☆ Star"}
                 </button>
                 {selectedArticle.link && <a href={selectedArticle.link} onClick={(e) => { e.preventDefault(); open(selectedArticle.link); }} style={{ fontSize: 13, color: "#8b5e3c", textDecoration: "none", fontFamily: "inherit", cursor: "pointer" }}>Open original ↗</a>}
+                {selectedArticle.updatedAt && <button onClick={() => (changes ? setChanges(null) : showChanges())} style={{ background: "none", border: "none", cursor: "pointer", fontSize: 13, fontFamily: "inherit", padding: "6px 0", color: "#8b5e3c" }}>{changes ? "Hide changes" : "Show changes"}</button>}
               </div>
-              <div className="article-body" onClick={(e) => { const anchor = e.target.closest("a"); if (anchor?.href) { e.preventDefault(); open(anchor.href); } }} dangerouslySetInnerHTML={{ __html: selectedArticle.content || "<p>No content available. Open the original article to read more.</p>" }} />
+              {episode && (
+                <div className="episode">
+                  {episode.image && <img src={episode.image} alt="" style={{ width: 72, height: 72, borderRadius: 8, objectFit: "cover", flexShrink: 0 }} />}
+                  <div style={{ flex: 1, minWidth: 0, display: "flex", flexDirection: "column", gap: 8 }}>
+                    <span style={{ fontSize: 12, color: "#8a7e6e" }}>
+                      {[episode.season && `Season ${episode.season}`, episode.episode && `Episode ${episode.episode}`, episode.enclosures[0]?.duration && formatDuration(episode.enclosures[0].duration), episode.explicit && "Explicit"].filter(Boolean).join(" · ")}
+                    </span>
+                    {episode.enclosures.map((enc) => {
+                      const dl = downloads[enc.id];
+                      const src = dl?.status === "done" && dl.path ? convertFileSrc(dl.path) : enc.url;
+                      const played = () => invoke("mark_episode_played", { enclosureId: enc.id }).catch(() => {});
+                      return (
+                        <div key={enc.id} style={{ display: "flex", flexDirection: "column", gap: 6 }}>
+                          {enc.mimeType?.startsWith("video/")
+                            ? <video controls preload="none" src={src} onEnded={played} style={{ width: "100%", borderRadius: 6 }} />
+                            : <audio controls preload="none" src={src} onEnded={played} style={{ width: "100%" }} />}
+                          <div className="download">
+                            {!dl && <button onClick={() => downloadEnclosure(enc.id)}>Download</button>}
+                            {(dl?.status === "queued" || dl?.status === "downloading") && <span>{dl.totalBytes ? `Downloading ${Math.floor((dl.bytesDownloaded / dl.totalBytes) * 100)}%` : dl.status === "queued" ? "Queued" : `Downloading ${formatBytes(dl.bytesDownloaded)}`}</span>}
+                            {dl?.status === "done" && <span>Downloaded{dl.totalBytes ? ` · ${formatBytes(dl.totalBytes)}` : ""}</span>}
+                            {dl?.status === "failed" && <><span title={dl.error || ""} style={{ color: "#b04a3a" }}>Download failed</span><button onClick={() => downloadEnclosure(enc.id)}>Retry</button></>}
+                            {dl && <button onClick={() => deleteDownload(enc.id)}>{dl.status === "done" ? "Delete" : "Cancel"}</button>}
+                          </div>
+                        </div>
+                      );
+                    })}
+                  </div>
+                </div>
+              )}
+              {changes && (
+                <div className="diff">
+                  {changes.map((chunk, i) => chunk.kind === "insert" ? <ins key={i}>{chunk.text}</ins> : chunk.kind === "delete" ? <del key={i}>{chunk.text}</del> : <span key={i}>{chunk.text}</span>)}
+                </div>
+              )}
+              {selectedArticle.fullContentError && !selectedArticle.fullContent && feeds.find((f) => f.url === selectedArticle.feedUrl)?.fullText && (
+                <div className="download" style={{ marginBottom: 18 }}>
+                  <span title={selectedArticle.fullContentError} style={{ color: "#b04a3a" }}>Could not fetch the full article</span>
+                  <button onClick={() => retryFullText(selectedArticle.id)}>Retry</button>
+                </div>
+              )}
+              <div className="article-body" onClick={(e) => { const anchor = e.target.closest("a"); if (anchor?.href) { e.preventDefault(); open(anchor.href); } }} dangerouslySetInnerHTML={{ __html: (feeds.find((f) => f.url === selectedArticle.feedUrl)?.fullText && selectedArticle.fullContent) || selectedArticle.content || "<p>No content available. Open the original article to read more.</p>" }} />
             </article>
           </div>
         ) : (
@@ -395,6 +575,7 @@ export default function RSSReader() {
                   <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center", marginBottom: 5 }}>
                     <span className="feed-tag">{article.feedName}</span>
                     <div style={{ display: "flex", alignItems: "center", gap: 6, flexShrink: 0 }}>
+                      {article.updatedAt && <span className="updated-tag">Updated</span>}
                       <span style={{ fontSize: 11, color: "#b0a690" }}>{formatDate(article.published)}</span>
                       <span
                         role="button"
@@ -407,6 +588,7 @@ export default function RSSReader() {
                       </span>
                     </div>
                   </div>
+                  {article.leadImage && <img className="thumb" src={article.leadImage} alt="" loading="lazy" onError={(e) => { e.currentTarget.style.display = "none"; }} />}
                   <h3 style={{
                     fontFamily: "'Newsreader', Georgia, serif", fontSize: isMobile ? 15 : 17,
                     fontWeight: article.is_read ? 400 : 500, lineHeight: 1.35,
@@ -438,6 +620,13 @@ const cssText = `
   * { margin: 0; padding: 0; box-sizing: border-box; -webkit-tap-highlight-color: transparent; }
 
   .badge { margin-left: auto; background: #8b5e3c; color: #faf7f2; font-size: 11px; font-weight: 600; padding: 1px 7px; border-radius: 10px; min-width: 20px; text-align: center; flex-shrink: 0; }
+  .updated-tag { font-size: 10px; font-weight: 600; color: #5a7a4a; background: #e6eedd; padding: 1px 6px; border-radius: 4px; text-transform: uppercase; letter-spacing: 0.04em; }
+  .episode { display: flex; gap: 14px; align-items: flex-start; background: #fff; border: 1px solid #e8e0d4; border-radius: 8px; padding: 12px; margin-bottom: 24px; }
+  .download { display: flex; align-items: center; gap: 10px; font-size: 12px; color: #8a7e6e; }
+  .download button { background: none; border: none; padding: 0; cursor: pointer; font-size: 12px; font-family: inherit; color: #8b5e3c; }
+  .diff { white-space: pre-wrap; font-size: 14px; line-height: 1.6; color: #3a3228; background: #fff; border: 1px solid #e8e0d4; border-radius: 8px; padding: 14px 16px; margin-bottom: 24px; }
+  .diff ins { background: #e6eedd; color: #2f5a22; text-decoration: none; }
+  .diff del { background: #f6e0dc; color: #8a3a2c; }
   .feed-tag { font-size: 11px; font-weight: 600; color: #8b5e3c; text-transform: uppercase; letter-spacing: 0.04em; }
 
   .primary-btn { padding: 9px 16px; background: #8b5e3c; color: #faf7f2; border: none; border-radius: 8px; font-size: 13px; font-weight: 500; cursor: pointer; font-family: inherit; transition: filter 0.15s; }
@@ -467,6 +656,9 @@ const cssText = `
   .topbar-btn:hover { background: #f0ebe3; }
 
   .article-card { display: block; width: 100%; text-align: left; padding: 14px 16px; border: 1px solid; border-radius: 10px; cursor: pointer; margin-top: 8px; font-family: inherit; transition: box-shadow 0.15s, transform 0.12s; }
+  .article-card::after { content: ""; display: block; clear: both; }
+  .thumb { float: right; width: 72px; height: 72px; object-fit: cover; border-radius: 6px; margin: 2px 0 4px 12px; background: #f0ebe3; }
+  .feed-icon { width: 16px; height: 16px; border-radius: 3px; object-fit: contain; flex-shrink: 0; }
   .article-card:hover { box-shadow: 0 2px 12px rgba(0,0,0,0.06); transform: translateY(-1px); }
   .article-card:active { transform: translateY(0); box-shadow: none; }
 
@@ -490,5 +682,6 @@ const cssText = `
     .remove-btn { opacity: 0.5 !important; }
     .article-body { font-size: 16px; line-height: 1.7; }
     .article-card { padding: 12px 14px; }
+    .thumb { width: 56px; height: 56px; }
   }
 `;
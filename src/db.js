@@ -1,4 +1,5 @@
 import Database from "@tauri-apps/plugin-sql";
+import { invoke } from "@tauri-apps/api/core";
 
 let db = null;
 
@@ -17,15 +18,8 @@ export async function initDb() {
 }
 
 export async function listFeeds() {
-  const rows = await db.select("SELECT url, name, added_at FROM feeds ORDER BY added_at ASC");
-  return rows.map((r) => ({ url: r.url, name: r.name, addedAt: new Date(r.added_at).toISOString() }));
-}
-
-export async function addFeed({ url, name, addedAt }) {
-  const ts = new Date(addedAt).getTime();
-  return withWriteLock(() =>
-    db.execute("INSERT OR IGNORE INTO feeds (url, name, added_at) VALUES ($1, $2, $3)", [url, name, ts])
-  );
+  const rows = await db.select("SELECT url, name, added_at, auto_download_keep, full_text FROM feeds ORDER BY added_at ASC");
+  return rows.map((r) => ({ url: r.url, name: r.name, addedAt: new Date(r.added_at).toISOString(), autoDownloadKeep: r.auto_download_keep, fullText: !!r.full_text }));
 }
 
 export async function removeFeed(url) {
@@ -35,7 +29,7 @@ export async function removeFeed(url) {
 }
 
 export async function listArticles({ feedUrl, filter } = {}) {
-  let sql = "SELECT id, feed_url, feed_name, title, link, published, published_ts, content, author, is_read, is_starred, fetched_at FROM articles";
+  let sql = "SELECT id, feed_url, feed_name, title, link, published, published_ts, content, author, is_read, is_starred, fetched_at, updated_at, lead_image, full_content, full_content_error FROM articles";
   const conditions = [];
   const params = [];
   let paramIdx = 1;
@@ -71,40 +65,13 @@ function rowToArticle(r) {
     author: r.author,
     is_read: !!r.is_read,
     is_starred: !!r.is_starred,
+    updatedAt: r.updated_at,
+    leadImage: r.lead_image,
+    fullContent: r.full_content,
+    fullContentError: r.full_content_error,
   };
 }
 
-export async function upsertArticles(feedUrl, feedName, items) {
-  return withWriteLock(async () => {
-    const now = Date.now();
-    for (const item of items) {
-      const id = `${feedUrl}::${item.link || item.title}`;
-      const publishedTs = item.published ? new Date(item.published).getTime() || 0 : 0;
-      await db.execute(
-        `INSERT INTO articles (id, feed_url, feed_name, title, link, published, published_ts, content, author, fetched_at)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-         ON CONFLICT(id) DO UPDATE SET
-           feed_name = $3, title = $4, link = $5, published = $6, published_ts = $7,
-           content = $8, author = $9, fetched_at = $10`,
-        [id, feedUrl, feedName, item.title, item.link, item.published, publishedTs, item.content, item.author, now]
-      );
-    }
-    // Prune per-feed (not globally) so adding/refreshing one feed can't wipe another
-    await db.execute(
-      `DELETE FROM articles
-       WHERE is_starred = 0
-         AND feed_url = $1
-         AND id NOT IN (
-           SELECT id FROM articles
-           WHERE is_starred = 0 AND feed_url = $1
-           ORDER BY published_ts DESC, fetched_at DESC
-           LIMIT 500
-         )`,
-      [feedUrl]
-    );
-  });
-}
-
 export async function markRead(articleId) {
   return withWriteLock(() =>
     db.execute("UPDATE articles SET is_read = 1 WHERE id = $1", [articleId])
@@ -162,12 +129,16 @@ export async function importFromLocalStorageIfNeeded() {
         const publishedTs = a.published ? new Date(a.published).getTime() || 0 : 0;
         const isRead = legacyRead && legacyRead[a.id] ? 1 : 0;
         const isStarred = legacyStarred && legacyStarred[a.id] ? 1 : 0;
+        // Content from localStorage was never sanitized; run it through the same pass as ingest.
+        const content = a.content ? await invoke("prepare_content", { html: a.content, base: a.link || null }) : a.content;
         await db.execute(
           `INSERT OR IGNORE INTO articles (id, feed_url, feed_name, title, link, published, published_ts, content, author, is_read, is_starred, fetched_at)
            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)`,
-          [a.id, a.feedUrl, a.feedName, a.title, a.link, a.published, publishedTs, a.content, a.author, isRead, isStarred, now]
+          [a.id, a.feedUrl, a.feedName, a.title, a.link, a.published, publishedTs, content, a.author, isRead, isStarred, now]
         );
       }
+      // These rows keep their old link-or-title ids; have the next refresh of each feed carry them over to guid-based ids.
+      await db.execute("UPDATE feeds SET legacy_ids = 1 WHERE url IN (SELECT feed_url FROM articles WHERE content_hash IS NULL)");
     }
 
     await db.execute("INSERT OR REPLACE INTO meta (key, value) VALUES ('migrated_from_localstorage', '1')");